@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum OrbitAction {
+    Forward,
+    Left,
+    Right,
+    Backward,
+
+    Cw,
+    Ccw,
+
+    TiltUp,
+    TiltDown,
+
+    ZoomIn,
+    ZoomOut,
+
+    DragRotate,
+    DragPan,
+
+    /// Saves the view to the numbered slot in [`OrbitCamBookmarks`](crate::OrbitCamBookmarks).
+    StoreBookmark(u8),
+    /// Returns to the view saved in the numbered slot.
+    RecallBookmark(u8),
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+    /// Active while every modifier key is held along with the inner binding.
+    Chord(Vec<KeyCode>, Box<InputBinding>),
+}
+
+impl InputBinding {
+    pub fn chord(modifiers: impl IntoIterator<Item = KeyCode>, binding: InputBinding) -> Self {
+        InputBinding::Chord(modifiers.into_iter().collect(), Box::new(binding))
+    }
+}
+
+impl From<KeyCode> for InputBinding {
+    fn from(key: KeyCode) -> Self {
+        InputBinding::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputBinding {
+    fn from(button: MouseButton) -> Self {
+        InputBinding::Mouse(button)
+    }
+}
+
+impl From<GamepadButton> for InputBinding {
+    fn from(button: GamepadButton) -> Self {
+        InputBinding::Gamepad(button)
+    }
+}
+
+/// Maps each [`OrbitAction`] to any number of [`InputBinding`]s.
+///
+/// Serializes as a list of pairs sorted by action rather than a map, so
+/// formats which only allow string keys can store it and saves are stable.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(
+    from = "Vec<(OrbitAction, Vec<InputBinding>)>",
+    into = "Vec<(OrbitAction, Vec<InputBinding>)>"
+)]
+pub struct ActionMap(HashMap<OrbitAction, Vec<InputBinding>>);
+
+impl From<Vec<(OrbitAction, Vec<InputBinding>)>> for ActionMap {
+    fn from(bindings: Vec<(OrbitAction, Vec<InputBinding>)>) -> Self {
+        ActionMap(bindings.into_iter().collect())
+    }
+}
+
+impl From<ActionMap> for Vec<(OrbitAction, Vec<InputBinding>)> {
+    fn from(map: ActionMap) -> Self {
+        let mut bindings = map.0.into_iter().collect::<Vec<_>>();
+        bindings.sort_by_key(|(action, _)| *action);
+        bindings
+    }
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bindings `OrbitCamConfig` used to hardcode, plus the gamepad triggers
+    /// for zooming and bookmarks on the digit keys, held with control to store.
+    pub fn standard() -> Self {
+        let digits = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+
+        let mut map = Self::new()
+            .with(OrbitAction::Forward, KeyCode::KeyW)
+            .with(OrbitAction::Left, KeyCode::KeyA)
+            .with(OrbitAction::Right, KeyCode::KeyD)
+            .with(OrbitAction::Backward, KeyCode::KeyS)
+            .with(OrbitAction::Cw, KeyCode::ArrowLeft)
+            .with(OrbitAction::Ccw, KeyCode::ArrowRight)
+            .with(OrbitAction::TiltUp, KeyCode::ArrowUp)
+            .with(OrbitAction::TiltDown, KeyCode::ArrowDown)
+            .with(OrbitAction::ZoomIn, KeyCode::ShiftLeft)
+            .with(OrbitAction::ZoomIn, GamepadButton::RightTrigger2)
+            .with(OrbitAction::ZoomOut, KeyCode::Space)
+            .with(OrbitAction::ZoomOut, GamepadButton::LeftTrigger2)
+            .with(OrbitAction::DragRotate, MouseButton::Right)
+            .with(OrbitAction::DragPan, MouseButton::Left);
+
+        for (slot, digit) in (1..).zip(digits) {
+            map.bind(
+                OrbitAction::StoreBookmark(slot),
+                InputBinding::chord([KeyCode::ControlLeft], digit.into()),
+            );
+            map.bind(OrbitAction::RecallBookmark(slot), digit);
+        }
+
+        map
+    }
+
+    pub fn with(mut self, action: OrbitAction, binding: impl Into<InputBinding>) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    pub fn bind(&mut self, action: OrbitAction, binding: impl Into<InputBinding>) -> &mut Self {
+        self.0.entry(action).or_default().push(binding.into());
+        self
+    }
+
+    pub fn unbind(&mut self, action: OrbitAction) -> Vec<InputBinding> {
+        self.0.remove(&action).unwrap_or_default()
+    }
+
+    pub fn bindings(&self, action: OrbitAction) -> &[InputBinding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The actions with at least one binding, in no particular order.
+    pub fn actions(&self) -> impl Iterator<Item = OrbitAction> + '_ {
+        self.0.keys().copied()
+    }
+}
+
+/// The input state needed to evaluate an [`ActionMap`].
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl ActionInput<'_, '_> {
+    /// How strongly `binding` is held, between `0.0` and `1.0`.
+    ///
+    /// Keys and mouse buttons are either fully held or not, while gamepad
+    /// buttons report their analog value, so triggers can be bound to zooming.
+    pub fn binding_value(&self, binding: &InputBinding) -> f32 {
+        match binding {
+            InputBinding::Key(key) => self.keys.pressed(*key) as u8 as f32,
+            InputBinding::Mouse(button) => self.mouse.pressed(*button) as u8 as f32,
+            InputBinding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .filter_map(|gamepad| gamepad.get(*button))
+                .fold(0.0, f32::max),
+            InputBinding::Chord(modifiers, binding) => {
+                if self.keys.all_pressed(modifiers.iter().copied()) {
+                    self.binding_value(binding)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    pub fn value(&self, map: &ActionMap, action: OrbitAction) -> f32 {
+        map.bindings(action)
+            .iter()
+            .map(|binding| self.binding_value(binding))
+            .fold(0.0, f32::max)
+    }
+
+    pub fn pressed(&self, map: &ActionMap, action: OrbitAction) -> bool {
+        self.value(map, action) > 0.0
+    }
+
+    /// Whether `binding` started being held this frame.
+    pub fn binding_just_pressed(&self, binding: &InputBinding) -> bool {
+        match binding {
+            InputBinding::Key(key) => self.keys.just_pressed(*key),
+            InputBinding::Mouse(button) => self.mouse.just_pressed(*button),
+            InputBinding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(*button)),
+            InputBinding::Chord(modifiers, binding) => {
+                self.keys.all_pressed(modifiers.iter().copied())
+                    && self.binding_just_pressed(binding)
+            }
+        }
+    }
+
+    pub fn just_pressed(&self, map: &ActionMap, action: OrbitAction) -> bool {
+        map.bindings(action)
+            .iter()
+            .any(|binding| self.binding_just_pressed(binding))
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::OrbitCam;
+
+/// How a camera orbiting a [`OrbitBody`] follows it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub enum OrbitFrame {
+    /// Moves and rotates with the body, staying over the same spot as it spins.
+    #[default]
+    BodyFixed,
+    /// Moves with the body but ignores its rotation.
+    Inertial,
+}
+
+/// An entity whose transform an [`OrbitCam`] orbits in, such as a planet.
+///
+/// The camera's state and focus point are then relative to the body.
+#[derive(Copy, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub struct OrbitBody {
+    pub entity: Entity,
+    pub frame: OrbitFrame,
+}
+
+/// Updates the `GlobalTransform` of orbit cameras and everything under them
+/// after they move, as they're moved after transform propagation so they can
+/// follow their bodies exactly.
+///
+/// Cameras can be children of any entity, including their body, as their
+/// `Transform` is kept relative to their parent.
+pub fn propagate_orbitcams(
+    cameras: Query<(Entity, Option<&Parent>), With<OrbitCam>>,
+    mut transforms: Query<(&Transform, &mut GlobalTransform)>,
+    children: Query<&Children>,
+) {
+    for (camera, parent) in cameras.iter() {
+        let parent = parent
+            .and_then(|parent| transforms.get(parent.get()).ok())
+            .map_or(GlobalTransform::IDENTITY, |(_, global)| *global);
+        propagate_from(camera, parent, &mut transforms, &children);
+    }
+}
+
+fn propagate_from(
+    entity: Entity,
+    parent: GlobalTransform,
+    transforms: &mut Query<(&Transform, &mut GlobalTransform)>,
+    children: &Query<&Children>,
+) {
+    let Ok((transform, mut global)) = transforms.get_mut(entity) else {
+        return;
+    };
+    let updated = parent.mul_transform(*transform);
+    *global = updated;
+
+    for child in children.get(entity).into_iter().flatten() {
+        propagate_from(*child, updated, transforms, children);
+    }
+}
+
+/// The `Transform` placing a camera at the world space `transform`, which is
+/// relative to its `parent` if it has one.
+pub(crate) fn relative_to_parent(
+    transform: Transform,
+    parent: Option<&Parent>,
+    transforms: &Query<&GlobalTransform>,
+) -> Transform {
+    match parent.and_then(|parent| transforms.get(parent.get()).ok()) {
+        Some(parent) => GlobalTransform::from(transform).reparented_to(parent),
+        None => transform,
+    }
+}
+
+impl OrbitCam {
+    /// The world transform the output of [`drive`](Self::drive) is relative to.
+    ///
+    /// This is the identity without a body, or while the body has no `GlobalTransform`.
+    pub fn body_transform(&self, transforms: &Query<&GlobalTransform>) -> Transform {
+        let Some(body) = self.body else {
+            return Transform::IDENTITY;
+        };
+        let Ok(global) = transforms.get(body.entity) else {
+            return Transform::IDENTITY;
+        };
+
+        let (_, rotation, translation) = global.to_scale_rotation_translation();
+        match body.frame {
+            OrbitFrame::BodyFixed => {
+                Transform::from_translation(translation).with_rotation(rotation)
+            }
+            OrbitFrame::Inertial => Transform::from_translation(translation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        transform::systems::{propagate_transforms, sync_simple_transforms},
+    };
+
+    use super::*;
+    use crate::{update_orbitcams, OrbitFlightFinished};
+
+    #[test]
+    fn keeps_up_with_a_moving_body() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<OrbitFlightFinished>>();
+        let body = world.spawn(Transform::IDENTITY).id();
+        let camera = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam {
+                    body: Some(OrbitBody {
+                        entity: body,
+                        frame: OrbitFrame::Inertial,
+                    }),
+                    ..default()
+                },
+            ))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                sync_simple_transforms,
+                propagate_transforms,
+                update_orbitcams,
+                propagate_orbitcams,
+            )
+                .chain(),
+        );
+
+        let arm = world.get::<OrbitCam>(camera).unwrap().target_transform();
+        for _ in 0..3 {
+            world.get_mut::<Transform>(body).unwrap().translation.x += 1.0;
+            schedule.run(&mut world);
+
+            let body = *world.get::<Transform>(body).unwrap();
+            let global = world.get::<GlobalTransform>(camera).unwrap();
+            assert!(global
+                .translation()
+                .abs_diff_eq((body * arm).translation, 1e-4));
+        }
+    }
+
+    #[test]
+    fn children_of_their_body_are_only_moved_by_it_once() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<OrbitFlightFinished>>();
+        let body_transform = Transform::from_xyz(5.0, 2.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(0.6))
+            .with_scale(Vec3::splat(2.0));
+        let body = world.spawn(body_transform).id();
+        let camera = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam {
+                    body: Some(OrbitBody {
+                        entity: body,
+                        frame: OrbitFrame::BodyFixed,
+                    }),
+                    ..default()
+                },
+            ))
+            .set_parent(body)
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                sync_simple_transforms,
+                propagate_transforms,
+                update_orbitcams,
+                propagate_orbitcams,
+            )
+                .chain(),
+        );
+        schedule.run(&mut world);
+
+        // The body's scale isn't part of the frame the camera orbits in.
+        let frame = body_transform.with_scale(Vec3::ONE);
+        let arm = world.get::<OrbitCam>(camera).unwrap().target_transform();
+        let global = world.get::<GlobalTransform>(camera).unwrap();
+        assert!(global
+            .translation()
+            .abs_diff_eq((frame * arm).translation, 1e-4));
+    }
+
+    #[test]
+    fn propagates_through_parents_and_children() {
+        let mut world = World::new();
+        let light = world.spawn(Transform::from_xyz(0.0, 0.0, 2.0)).id();
+        let camera = world
+            .spawn((Transform::from_xyz(1.0, 0.0, 0.0), OrbitCam::default()))
+            .add_child(light)
+            .id();
+        world
+            .spawn((
+                Transform::from_xyz(5.0, 0.0, 0.0),
+                GlobalTransform::from_xyz(5.0, 0.0, 0.0),
+            ))
+            .add_child(camera);
+
+        world.run_system_once(propagate_orbitcams).unwrap();
+
+        let global = |entity| world.get::<GlobalTransform>(entity).unwrap().translation();
+        assert_eq!(global(camera), Vec3::new(6.0, 0.0, 0.0));
+        assert_eq!(global(light), Vec3::new(6.0, 0.0, 2.0));
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "persist")]
+use crate::Persist;
+use crate::{
+    ActionInput, InputRouter, OrbitAction, OrbitCam, OrbitCamConfig, OrbitCamReplay, OrbitCamSlot,
+    OrbitCamState, RoutedOrbitCam,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Transition {
+    /// Eases towards the new view.
+    #[default]
+    Smooth,
+    /// Jumps straight to the new view.
+    Snap,
+}
+
+/// Named viewpoints which cameras can be sent back to.
+///
+/// Numbered slots used by the bookmark hotkeys are stored under their number,
+/// so `"1"` is the first slot.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrbitCamBookmarks {
+    pub views: BTreeMap<String, OrbitCamState>,
+}
+
+impl OrbitCamBookmarks {
+    /// Saves the view `camera` is easing towards under `name`.
+    pub fn store(&mut self, name: impl Into<String>, camera: &OrbitCam) {
+        self.views
+            .insert(name.into(), camera.state(OrbitCamSlot::Target));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OrbitCamState> {
+        self.views.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<OrbitCamState> {
+        self.views.remove(name)
+    }
+
+    /// Sends `camera` to the view saved under `name`, returning whether there was one.
+    pub fn recall(&self, name: &str, camera: &mut OrbitCam, transition: Transition) -> bool {
+        let Some(state) = self.get(name) else {
+            return false;
+        };
+
+        camera.transition_to(*state, transition);
+        true
+    }
+}
+
+#[cfg(feature = "persist")]
+impl Persist for OrbitCamBookmarks {}
+
+impl OrbitCam {
+    /// Sends the camera to `state`, cancelling any flight.
+    pub fn transition_to(&mut self, state: OrbitCamState, transition: Transition) {
+        match transition {
+            Transition::Smooth => {
+                self.cancel_flight();
+                self.set_state(state, OrbitCamSlot::Target);
+            }
+            Transition::Snap => self.snap_to(state),
+        }
+    }
+}
+
+pub fn process_bookmarks(
+    mut cameras: Query<RoutedOrbitCam, Without<OrbitCamReplay>>,
+    mut bookmarks: ResMut<OrbitCamBookmarks>,
+    input: ActionInput,
+    router: InputRouter,
+    config: Res<OrbitCamConfig>,
+) {
+    for mut routed in cameras.iter_mut() {
+        let cursor = routed.view.and_then(|view| router.cursor(view));
+        if !router.accepts(routed.entity, routed.view, routed.marked, cursor) {
+            continue;
+        }
+
+        let config = routed.config.unwrap_or(&config);
+
+        let mut stored = Vec::new();
+        let mut recalled = Vec::new();
+        for action in config.actions.actions() {
+            match action {
+                OrbitAction::StoreBookmark(slot) if input.just_pressed(&config.actions, action) => {
+                    stored.push(slot)
+                }
+                OrbitAction::RecallBookmark(slot)
+                    if input.just_pressed(&config.actions, action) =>
+                {
+                    recalled.push(slot)
+                }
+                _ => {}
+            }
+        }
+        // Bindings come out in no particular order, so sort them to keep
+        // several slots pressed at once deterministic.
+        stored.sort_unstable();
+        recalled.sort_unstable();
+
+        for slot in &stored {
+            bookmarks.store(slot.to_string(), &routed.orbit);
+        }
+        // Storing is usually a chord around the recall binding, so don't
+        // immediately recall what was just stored.
+        for slot in recalled.iter().filter(|slot| !stored.contains(slot)) {
+            if let Some(state) = bookmarks.get(&slot.to_string()) {
+                routed.recall(*state, config.bookmark_transition);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{testing::input_world, OrbitCamInput, OrbitCamRecorder};
+
+    #[test]
+    fn records_recalled_bookmarks() {
+        let mut world = input_world(OrbitCamConfig {
+            bookmark_transition: Transition::Snap,
+            ..default()
+        });
+
+        let mut saved = OrbitCam::default();
+        saved.distance.hard_set(42.0);
+        let mut bookmarks = OrbitCamBookmarks::default();
+        bookmarks.store("1", &saved);
+        world.insert_resource(bookmarks);
+
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::Digit1);
+        world.insert_resource(keys);
+
+        let mut recorder = OrbitCamRecorder::default();
+        recorder.log.frames.push(default());
+        let camera = world.spawn((OrbitCam::default(), recorder)).id();
+
+        world.run_system_once(process_bookmarks).unwrap();
+
+        assert_eq!(
+            world.get::<OrbitCam>(camera).unwrap().distance.current,
+            42.0
+        );
+        assert_eq!(
+            world.get::<OrbitCamRecorder>(camera).unwrap().log.frames[0].inputs,
+            [OrbitCamInput::Recall {
+                state: saved.state(OrbitCamSlot::Target),
+                transition: Transition::Snap,
+            }]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::{
+    ecs::system::{StaticSystemParam, SystemParam},
+    math::Affine3A,
+    prelude::*,
+    render::primitives::Aabb,
+};
+use buttery::{TransformComponent, Translate};
+
+use crate::OrbitCam;
+
+/// Finds what's between an orbit camera and the point it orbits.
+///
+/// Implement this on a [`SystemParam`] and add [`avoid_collisions`] for it
+/// to use another source of colliders, such as a physics engine.
+pub trait CollisionQuery {
+    /// The distance along `ray` to the first thing within `max_distance` that
+    /// a sphere of `radius` would hit, ignoring `camera`.
+    fn cast(&self, ray: Ray3d, max_distance: f32, radius: f32, camera: Entity) -> Option<f32>;
+}
+
+/// Pulls a camera in towards its orbit point when something is in the way.
+#[derive(Component, Debug)]
+pub struct OrbitCamOcclusion {
+    /// How far to keep the camera from whatever is in the way.
+    pub radius: f32,
+    /// The fraction of the way from the orbit point to the camera it's pulled in to.
+    pub pull: TransformComponent<Translate<f32>>,
+}
+
+impl Default for OrbitCamOcclusion {
+    fn default() -> Self {
+        OrbitCamOcclusion {
+            radius: 0.2,
+            pull: TransformComponent::new(0.01, 1.0),
+        }
+    }
+}
+
+/// Marks an entity with an [`Aabb`] as something cameras avoid with [`AabbObstacles`].
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct OrbitCamObstacle;
+
+/// The default [`CollisionQuery`], against the bounding boxes of entities
+/// marked with [`OrbitCamObstacle`].
+#[derive(SystemParam)]
+pub struct AabbObstacles<'w, 's> {
+    obstacles:
+        Query<'w, 's, (Entity, &'static Aabb, &'static GlobalTransform), With<OrbitCamObstacle>>,
+}
+
+impl CollisionQuery for AabbObstacles<'_, '_> {
+    fn cast(&self, ray: Ray3d, max_distance: f32, radius: f32, camera: Entity) -> Option<f32> {
+        self.obstacles
+            .iter()
+            .filter(|(entity, ..)| *entity != camera)
+            .filter_map(|(_, aabb, transform)| {
+                // Distances along the ray are the same in the box's own space
+                // as long as the direction isn't renormalized.
+                let to_local = transform.affine().inverse();
+                let origin = to_local.transform_point3(ray.origin);
+                let direction = to_local.transform_vector3(*ray.direction);
+                let padding = radius / transform.scale();
+
+                let min = Vec3::from(aabb.min()) - padding;
+                let max = Vec3::from(aabb.max()) + padding;
+                ray_box(origin, direction, min, max)
+            })
+            .filter(|distance| *distance < max_distance)
+            .min_by(f32::total_cmp)
+    }
+}
+
+/// The distance to where `origin + direction * t` enters the box. Boxes the
+/// ray starts inside are ignored, so cameras can orbit points on obstacles.
+fn ray_box(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let inverse = direction.recip();
+    let near = (min - origin) * inverse;
+    let far = (max - origin) * inverse;
+
+    let enter = near.min(far).max_element();
+    let exit = near.max(far).min_element();
+
+    (enter >= 0.0 && exit >= enter).then_some(enter)
+}
+
+impl OrbitCam {
+    /// The point the camera orbits and looks at, relative to its body.
+    pub fn orbit_point(&self) -> Vec3 {
+        let up = self.surface.constrain(self.up.current);
+        self.focus_position.current
+            + self
+                .surface
+                .place(up, self.target_height.current, Vec3::ZERO, self.min.current)
+    }
+}
+
+/// Pulls cameras with [`OrbitCamOcclusion`] in past anything `C` finds
+/// between them and their orbit point.
+///
+/// [`OrbitCamPlugin`](crate::OrbitCamPlugin) adds this for [`AabbObstacles`].
+/// Others should run in `PostUpdate` after [`replay_orbitcams`](crate::replay_orbitcams)
+/// and before [`propagate_orbitcams`](crate::propagate_orbitcams).
+pub fn avoid_collisions<C>(
+    mut cameras: Query<(
+        Entity,
+        &mut Transform,
+        &OrbitCam,
+        &mut OrbitCamOcclusion,
+        Option<&Parent>,
+    )>,
+    collisions: StaticSystemParam<C>,
+    transforms: Query<&GlobalTransform>,
+    time: Res<Time>,
+) where
+    C: SystemParam + 'static,
+    for<'w, 's> C::Item<'w, 's>: CollisionQuery,
+{
+    for (entity, mut transform, orbcam, mut occlusion, parent) in cameras.iter_mut() {
+        // Casts in world space, as the camera's transform may be relative to a parent.
+        let parent = parent
+            .and_then(|parent| transforms.get(parent.get()).ok())
+            .map_or(Affine3A::IDENTITY, GlobalTransform::affine);
+        let origin = orbcam.body_transform(&transforms) * orbcam.orbit_point();
+        let arm = parent.transform_point3(transform.translation) - origin;
+
+        occlusion.pull.target = match Dir3::new(arm) {
+            Ok(direction) => {
+                let length = arm.length();
+                collisions
+                    .cast(
+                        Ray3d::new(origin, direction),
+                        length,
+                        occlusion.radius,
+                        entity,
+                    )
+                    .map_or(1.0, |distance| distance / length)
+            }
+            Err(_) => 1.0,
+        };
+
+        let pull = occlusion.pull.drive(time.delta_secs());
+        transform.translation = parent.inverse().transform_point3(origin + arm * pull);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn rays_enter_boxes_in_front_of_them() {
+        let (min, max) = (Vec3::splat(-1.0), Vec3::splat(1.0));
+
+        let hit = ray_box(Vec3::new(0.0, 0.0, -5.0), Vec3::Z, min, max);
+        assert_eq!(hit, Some(4.0));
+        assert_eq!(ray_box(Vec3::new(3.0, 0.0, -5.0), Vec3::Z, min, max), None);
+        assert_eq!(ray_box(Vec3::new(0.0, 0.0, 5.0), Vec3::Z, min, max), None);
+        assert_eq!(ray_box(Vec3::ZERO, Vec3::Z, min, max), None);
+    }
+
+    fn cast(world: &mut World, direction: Dir3, camera: Entity) -> Option<f32> {
+        world
+            .run_system_once(move |obstacles: AabbObstacles| {
+                obstacles.cast(Ray3d::new(Vec3::ZERO, direction), 10.0, 0.5, camera)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn casts_against_padded_scaled_boxes() {
+        let mut world = World::new();
+        let camera = world.spawn_empty().id();
+        let obstacle = world
+            .spawn((
+                OrbitCamObstacle,
+                Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+                GlobalTransform::from(
+                    Transform::from_xyz(0.0, 0.0, 5.0).with_scale(Vec3::splat(2.0)),
+                ),
+            ))
+            .id();
+
+        // The box spans 3 to 7 along z, padded by the radius in world space.
+        let hit = cast(&mut world, Dir3::Z, camera).unwrap();
+        assert!((hit - 2.5).abs() < 1e-5);
+        assert_eq!(cast(&mut world, Dir3::X, camera), None);
+        assert_eq!(cast(&mut world, Dir3::Z, obstacle), None);
+    }
+
+    #[test]
+    fn pulls_blocked_cameras_in() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(0.1));
+        world.insert_resource(time);
+
+        let mut orbcam = OrbitCam::default();
+        let transform = orbcam.drive(0.0);
+        let origin = orbcam.orbit_point();
+        let arm = transform.translation - origin;
+        let camera = world
+            .spawn((transform, orbcam, OrbitCamOcclusion::default()))
+            .id();
+
+        let middle = origin + arm * 0.5;
+        world.spawn((
+            OrbitCamObstacle,
+            Aabb::from_min_max(Vec3::splat(-0.1), Vec3::splat(0.1)),
+            GlobalTransform::from_translation(middle),
+        ));
+
+        world
+            .run_system_once(avoid_collisions::<AabbObstacles>)
+            .unwrap();
+
+        let occlusion = world.get::<OrbitCamOcclusion>(camera).unwrap();
+        assert!(occlusion.pull.target < 0.5);
+        assert!(occlusion.pull.current < 1.0);
+        let pulled = world.get::<Transform>(camera).unwrap().translation;
+        assert!(pulled.distance(origin) < arm.length());
+        assert!((pulled - origin)
+            .normalize()
+            .abs_diff_eq(arm.normalize(), 1e-5));
+    }
+}
@@ -0,0 +1,236 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{up_from_geodetic, Geodetic, OrbitCam, OrbitCamSlot};
+
+/// An animated flight along the great circle between two views.
+///
+/// While a flight is running it overrides `up`, `distance` and `inclination`,
+/// so input has no effect until it arrives.
+#[derive(Copy, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub struct OrbitFlight {
+    from: Geodetic,
+    to: Geodetic,
+    /// Extra distance to pull back by at the midpoint of the flight.
+    pub peak: f32,
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+/// Sent when a camera's flight arrives.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct OrbitFlightFinished {
+    pub camera: Entity,
+}
+
+impl OrbitFlight {
+    pub fn new(from: Geodetic, to: Geodetic, radius: f32, duration: f32) -> Self {
+        let angle = direction(&from).angle_between(direction(&to));
+
+        OrbitFlight {
+            from,
+            to,
+            peak: angle * radius * 0.5,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// The view `fraction` of the way through the flight.
+    pub fn sample(&self, fraction: f32) -> Geodetic {
+        let t = fraction.clamp(0.0, 1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+
+        let (from, to) = (direction(&self.from), direction(&self.to));
+        let angle = from.angle_between(to);
+        let along = if angle < 1e-4 {
+            from.lerp(to, eased).normalize()
+        } else {
+            // Every great circle joins opposite points, so set off along the
+            // starting heading rather than an axis picked from rounding error.
+            let axis = if angle > PI - 1e-4 {
+                up_from_geodetic(self.from.latitude, self.from.longitude, self.from.heading)
+                    * Vec3::NEG_X
+            } else {
+                from.cross(to).normalize()
+            };
+            Quat::from_axis_angle(axis, eased * angle) * from
+        };
+        let latitude = along.y.clamp(-1.0, 1.0).asin();
+        let longitude = along.x.atan2(along.z);
+
+        let turn = (self.to.heading - self.from.heading + PI).rem_euclid(TAU) - PI;
+
+        Geodetic {
+            latitude,
+            longitude,
+            heading: self.from.heading + turn * eased,
+            tilt: self.from.tilt.lerp(self.to.tilt, eased),
+            altitude: self.from.altitude.lerp(self.to.altitude, eased) + self.peak * (t * PI).sin(),
+        }
+    }
+}
+
+fn direction(geodetic: &Geodetic) -> Vec3 {
+    up_from_geodetic(geodetic.latitude, geodetic.longitude, 0.0) * Vec3::Y
+}
+
+impl OrbitCam {
+    /// Flies from the current view to the given one over `duration` seconds,
+    /// pulling back mid-flight in proportion to how far it travels.
+    pub fn fly_to(
+        &mut self,
+        latitude: f32,
+        longitude: f32,
+        distance: f32,
+        heading: f32,
+        tilt: f32,
+        duration: f32,
+    ) {
+        let to = Geodetic {
+            latitude,
+            longitude,
+            heading,
+            tilt,
+            altitude: distance,
+        };
+
+        self.flight = Some(OrbitFlight::new(
+            self.geodetic(OrbitCamSlot::Current),
+            to,
+            self.surface.radius(self.target_height.target),
+            duration,
+        ));
+    }
+
+    pub fn cancel_flight(&mut self) {
+        self.flight = None;
+    }
+
+    /// Advances the flight, if any, by `time` seconds.
+    pub(crate) fn advance_flight(&mut self, time: f32) {
+        let Some(flight) = &mut self.flight else {
+            return;
+        };
+
+        flight.elapsed += time;
+        let view = if flight.finished() {
+            let to = flight.to;
+            self.flight = None;
+            to
+        } else {
+            flight.sample(flight.elapsed / flight.duration)
+        };
+
+        self.up.hard_set(up_from_geodetic(
+            view.latitude,
+            view.longitude,
+            view.heading,
+        ));
+        self.distance.hard_set(view.altitude);
+        self.inclination.hard_set(view.tilt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(latitude: f32, longitude: f32) -> Geodetic {
+        Geodetic {
+            latitude,
+            longitude,
+            heading: 0.3,
+            tilt: 0.2,
+            altitude: 10.0,
+        }
+    }
+
+    fn assert_direction(sampled: Geodetic, expected: Vec3) {
+        let sampled = direction(&sampled);
+        assert!(sampled.distance(expected) < 1e-4, "{sampled} != {expected}");
+    }
+
+    #[test]
+    fn starts_and_arrives_at_the_endpoints() {
+        let from = view(0.4, -1.0);
+        let to = Geodetic {
+            heading: 2.0,
+            tilt: 0.6,
+            altitude: 30.0,
+            ..view(-0.2, 2.5)
+        };
+        let flight = OrbitFlight::new(from, to, 100.0, 1.0);
+
+        let start = flight.sample(0.0);
+        assert_direction(start, direction(&from));
+        assert!((start.altitude - from.altitude).abs() < 1e-4);
+        assert!((start.tilt - from.tilt).abs() < 1e-6);
+
+        let end = flight.sample(1.0);
+        assert_direction(end, direction(&to));
+        assert!((end.altitude - to.altitude).abs() < 1e-3);
+        assert!((end.heading - to.heading).abs() < 1e-5);
+        assert!((end.tilt - to.tilt).abs() < 1e-6);
+    }
+
+    #[test]
+    fn peaks_halfway() {
+        let from = view(0.0, 0.0);
+        let to = view(0.0, 1.0);
+        let flight = OrbitFlight::new(from, to, 100.0, 1.0);
+
+        let middle = flight.sample(0.5);
+        assert!((flight.peak - 50.0).abs() < 1e-3);
+        assert!((middle.altitude - (from.altitude + flight.peak)).abs() < 1e-3);
+        assert!((middle.longitude - 0.5).abs() < 1e-4);
+        assert!(flight.sample(0.4).altitude < middle.altitude);
+        assert!(flight.sample(0.6).altitude < middle.altitude);
+    }
+
+    #[test]
+    fn flies_between_opposite_points() {
+        let from = view(0.0, 0.0);
+        let to = view(0.0, PI);
+        let flight = OrbitFlight::new(from, to, 100.0, 1.0);
+
+        for step in 0..=10 {
+            let sampled = direction(&flight.sample(step as f32 / 10.0));
+            assert!(sampled.is_finite());
+            assert!((sampled.length() - 1.0).abs() < 1e-4);
+        }
+
+        let middle = direction(&flight.sample(0.5));
+        assert!(middle.dot(direction(&from)).abs() < 1e-4);
+        assert!(middle.dot(direction(&to)).abs() < 1e-4);
+        assert_direction(flight.sample(1.0), direction(&to));
+
+        let north = OrbitFlight::new(
+            Geodetic {
+                heading: 0.0,
+                ..from
+            },
+            to,
+            100.0,
+            1.0,
+        );
+        assert!(north.sample(0.2).latitude > 0.1);
+        let east = OrbitFlight::new(
+            Geodetic {
+                heading: PI / 2.0,
+                ..from
+            },
+            to,
+            100.0,
+            1.0,
+        );
+        let early = east.sample(0.2);
+        assert!(early.latitude.abs() < 1e-4 && early.longitude > 0.1);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::{
+    ecs::entity::{VisitEntities, VisitEntitiesMut},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::OrbitCam;
+
+/// What an [`OrbitCam`] orbits around. Points are relative to the camera's body, if any.
+#[derive(Copy, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub enum OrbitFocus {
+    Point(Vec3),
+    /// Follows the entity's `GlobalTransform`, staying put if it has none.
+    Entity(Entity),
+}
+
+impl Default for OrbitFocus {
+    fn default() -> Self {
+        OrbitFocus::Point(Vec3::ZERO)
+    }
+}
+
+impl OrbitCam {
+    /// Moves `focus_position.target` to wherever `focus` currently is,
+    /// relative to the camera's `body`.
+    pub(crate) fn track_focus(&mut self, transforms: &Query<&GlobalTransform>, body: &Transform) {
+        let position = match self.focus {
+            OrbitFocus::Point(point) => Some(point),
+            OrbitFocus::Entity(entity) => transforms.get(entity).ok().map(|global| {
+                body.compute_affine()
+                    .inverse()
+                    .transform_point3(global.translation())
+            }),
+        };
+
+        if let Some(position) = position {
+            self.focus_position.target = position;
+        }
+    }
+}
+
+impl VisitEntities for OrbitCam {
+    fn visit_entities<F: FnMut(Entity)>(&self, mut f: F) {
+        if let OrbitFocus::Entity(entity) = self.focus {
+            f(entity);
+        }
+        if let Some(body) = self.body {
+            f(body.entity);
+        }
+    }
+}
+
+impl VisitEntitiesMut for OrbitCam {
+    fn visit_entities_mut<F: FnMut(&mut Entity)>(&mut self, mut f: F) {
+        if let OrbitFocus::Entity(entity) = &mut self.focus {
+            f(entity);
+        }
+        if let Some(body) = &mut self.body {
+            f(&mut body.entity);
+        }
+    }
+}
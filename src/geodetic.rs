@@ -0,0 +1,231 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use buttery::{Smoothed, TransformComponent};
+use serde::{Deserialize, Serialize};
+
+use crate::OrbitCam;
+
+/// Which value of a smoothed component to read or write.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum OrbitCamSlot {
+    /// The value the camera is easing towards.
+    #[default]
+    Target,
+    /// The value the camera is at right now. Writing only this eases back to the target.
+    Current,
+}
+
+impl OrbitCamSlot {
+    pub fn get<T: Smoothed>(self, component: &TransformComponent<T>) -> T::Attribute {
+        match self {
+            OrbitCamSlot::Target => component.target,
+            OrbitCamSlot::Current => component.current,
+        }
+    }
+
+    pub fn set<T: Smoothed>(self, component: &mut TransformComponent<T>, value: T::Attribute) {
+        match self {
+            OrbitCamSlot::Target => component.target = value,
+            OrbitCamSlot::Current => component.current = value,
+        }
+    }
+}
+
+/// An orbit camera's view in map terms, with all angles in radians.
+///
+/// The `+Y` axis is the north pole and the prime meridian passes through `+Z`,
+/// with longitude increasing towards `+X`.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub struct Geodetic {
+    pub latitude: f32,
+    pub longitude: f32,
+    /// Clockwise from north, so east is a quarter turn.
+    pub heading: f32,
+    /// The camera's `inclination`.
+    pub tilt: f32,
+    /// The camera's `distance`.
+    pub altitude: f32,
+}
+
+/// The `up` rotation which places the camera over `latitude` and `longitude`
+/// facing `heading`.
+pub fn up_from_geodetic(latitude: f32, longitude: f32, heading: f32) -> Quat {
+    surface_frame(latitude, longitude) * Quat::from_rotation_y(-heading)
+}
+
+/// The latitude, longitude and heading of an `up` rotation.
+///
+/// At the poles longitude is ambiguous, so it's reported as zero and the
+/// heading makes up the difference.
+pub fn geodetic_from_up(up: Quat) -> (f32, f32, f32) {
+    let direction = up * Vec3::Y;
+    let latitude = direction.y.clamp(-1.0, 1.0).asin();
+    // Rounding leaves a tiny horizontal offset at the poles, whose angle is arbitrary.
+    let longitude = if direction.xz().length() > 1e-6 {
+        direction.x.atan2(direction.z)
+    } else {
+        0.0
+    };
+
+    let twist = surface_frame(latitude, longitude).inverse() * up;
+    let forward = twist * Vec3::NEG_Z;
+    let heading = forward.x.atan2(-forward.z);
+
+    (latitude, longitude, heading)
+}
+
+fn surface_frame(latitude: f32, longitude: f32) -> Quat {
+    Quat::from_rotation_y(longitude) * Quat::from_rotation_x(FRAC_PI_2 - latitude)
+}
+
+impl OrbitCam {
+    pub fn geodetic(&self, slot: OrbitCamSlot) -> Geodetic {
+        let (latitude, longitude, heading) = geodetic_from_up(slot.get(&self.up));
+
+        Geodetic {
+            latitude,
+            longitude,
+            heading,
+            tilt: slot.get(&self.inclination),
+            altitude: slot.get(&self.distance),
+        }
+    }
+
+    pub fn set_geodetic(&mut self, geodetic: Geodetic, slot: OrbitCamSlot) {
+        let up = up_from_geodetic(geodetic.latitude, geodetic.longitude, geodetic.heading);
+        slot.set(&mut self.up, up);
+        slot.set(&mut self.inclination, geodetic.tilt);
+        slot.set(&mut self.distance, geodetic.altitude);
+    }
+
+    pub fn latitude(&self, slot: OrbitCamSlot) -> f32 {
+        self.geodetic(slot).latitude
+    }
+
+    pub fn longitude(&self, slot: OrbitCamSlot) -> f32 {
+        self.geodetic(slot).longitude
+    }
+
+    pub fn heading(&self, slot: OrbitCamSlot) -> f32 {
+        self.geodetic(slot).heading
+    }
+
+    pub fn tilt(&self, slot: OrbitCamSlot) -> f32 {
+        slot.get(&self.inclination)
+    }
+
+    pub fn altitude(&self, slot: OrbitCamSlot) -> f32 {
+        slot.get(&self.distance)
+    }
+
+    /// Moves the camera over `latitude` and `longitude`, keeping its heading.
+    pub fn set_lat_lon(&mut self, latitude: f32, longitude: f32, slot: OrbitCamSlot) {
+        let heading = self.heading(slot);
+        slot.set(&mut self.up, up_from_geodetic(latitude, longitude, heading));
+    }
+
+    pub fn set_heading(&mut self, heading: f32, slot: OrbitCamSlot) {
+        let (latitude, longitude, _) = geodetic_from_up(slot.get(&self.up));
+        slot.set(&mut self.up, up_from_geodetic(latitude, longitude, heading));
+    }
+
+    pub fn set_tilt(&mut self, tilt: f32, slot: OrbitCamSlot) {
+        slot.set(&mut self.inclination, tilt);
+    }
+
+    pub fn set_altitude(&mut self, altitude: f32, slot: OrbitCamSlot) {
+        slot.set(&mut self.distance, altitude);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use super::*;
+
+    fn same_angle(a: f32, b: f32) -> bool {
+        ((a - b + PI).rem_euclid(TAU) - PI).abs() < 1e-4
+    }
+
+    #[test]
+    fn round_trips_latitude_longitude_and_heading() {
+        for latitude in [-1.4, -0.7, 0.0, 0.3, 1.2] {
+            for longitude in [-3.0, -1.5, 0.0, 0.4, 2.9] {
+                for heading in [-2.5, 0.0, 1.0, 3.1] {
+                    let up = up_from_geodetic(latitude, longitude, heading);
+                    let (lat, lon, head) = geodetic_from_up(up);
+                    assert!((lat - latitude).abs() < 1e-4);
+                    assert!(same_angle(lon, longitude));
+                    assert!(same_angle(head, heading));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn faces_north_from_the_prime_meridian() {
+        let up = up_from_geodetic(0.0, 0.0, 0.0);
+        assert!((up * Vec3::Y).abs_diff_eq(Vec3::Z, 1e-6));
+        assert!((up * Vec3::NEG_Z).abs_diff_eq(Vec3::Y, 1e-6));
+
+        let east = up_from_geodetic(0.0, FRAC_PI_2, 0.0);
+        assert!((east * Vec3::Y).abs_diff_eq(Vec3::X, 1e-6));
+        let facing_east = up_from_geodetic(0.0, 0.0, FRAC_PI_2);
+        assert!((facing_east * Vec3::NEG_Z).abs_diff_eq(Vec3::X, 1e-6));
+    }
+
+    #[test]
+    fn poles_keep_the_rotation() {
+        for latitude in [FRAC_PI_2, -FRAC_PI_2] {
+            let up = up_from_geodetic(latitude, 1.0, 0.3);
+            let (lat, lon, heading) = geodetic_from_up(up);
+
+            assert!((lat - latitude).abs() < 1e-3);
+            assert_eq!(lon, 0.0);
+            assert!(up_from_geodetic(lat, lon, heading).angle_between(up) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn setters_only_touch_their_slot() {
+        let geodetic = Geodetic {
+            latitude: 0.5,
+            longitude: -1.0,
+            heading: 0.8,
+            tilt: 0.4,
+            altitude: 9.0,
+        };
+
+        for (slot, other) in [
+            (OrbitCamSlot::Target, OrbitCamSlot::Current),
+            (OrbitCamSlot::Current, OrbitCamSlot::Target),
+        ] {
+            let mut orbcam = OrbitCam::default();
+            let untouched = orbcam.state(other);
+
+            orbcam.set_geodetic(geodetic, slot);
+            let read = orbcam.geodetic(slot);
+            assert!((read.latitude - 0.5).abs() < 1e-5);
+            assert!(same_angle(read.longitude, -1.0));
+            assert!(same_angle(read.heading, 0.8));
+            assert_eq!((read.tilt, read.altitude), (0.4, 9.0));
+
+            orbcam.set_lat_lon(-0.2, 2.0, slot);
+            assert!((orbcam.latitude(slot) + 0.2).abs() < 1e-5);
+            assert!(same_angle(orbcam.longitude(slot), 2.0));
+            assert!(same_angle(orbcam.heading(slot), 0.8));
+
+            orbcam.set_heading(-1.5, slot);
+            assert!((orbcam.latitude(slot) + 0.2).abs() < 1e-5);
+            assert!(same_angle(orbcam.heading(slot), -1.5));
+
+            orbcam.set_tilt(0.1, slot);
+            orbcam.set_altitude(3.0, slot);
+            assert_eq!((orbcam.tilt(slot), orbcam.altitude(slot)), (0.1, 3.0));
+
+            assert_eq!(orbcam.state(other), untouched);
+        }
+    }
+}
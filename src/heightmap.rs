@@ -0,0 +1,318 @@
+use std::{
+    error::Error,
+    f32::consts::{PI, TAU},
+    fmt,
+};
+
+use bevy::{image::TextureAccessError, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::SurfaceHeight;
+
+/// How a heightmap image wraps around a sphere.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum HeightmapLayout {
+    /// Longitude across and latitude down, with north at the top, matching
+    /// [`Geodetic`](crate::Geodetic).
+    #[default]
+    Equirectangular,
+    /// Six square faces stacked vertically in the order `+X`, `-X`, `+Y`,
+    /// `-Y`, `+Z`, `-Z`, as bevy expects for cube textures.
+    Cube,
+}
+
+/// Why an image couldn't be read as a [`HeightmapSurface`].
+#[derive(Debug)]
+pub enum HeightmapError {
+    /// The image has no pixels.
+    Empty,
+    /// A [`HeightmapLayout::Cube`] image isn't six square faces tall.
+    NotCube(UVec2),
+    Access(TextureAccessError),
+}
+
+impl fmt::Display for HeightmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeightmapError::Empty => write!(f, "heightmap image is empty"),
+            HeightmapError::NotCube(size) => write!(
+                f,
+                "cube heightmap must be six times as tall as it is wide, but is {}x{}",
+                size.x, size.y
+            ),
+            HeightmapError::Access(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for HeightmapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HeightmapError::Access(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TextureAccessError> for HeightmapError {
+    fn from(error: TextureAccessError) -> Self {
+        HeightmapError::Access(error)
+    }
+}
+
+/// A [`SurfaceHeight`] sampled bilinearly from the red channel of an image.
+///
+/// The pixels are copied out when it's created, so it doesn't update if the
+/// image changes.
+#[derive(Clone, Debug)]
+pub struct HeightmapSurface {
+    pub layout: HeightmapLayout,
+    /// Height where the image is black.
+    pub base: f32,
+    /// Height added where the image is white.
+    pub scale: f32,
+    size: UVec2,
+    samples: Vec<f32>,
+}
+
+impl HeightmapSurface {
+    pub fn from_image(
+        image: &Image,
+        layout: HeightmapLayout,
+        base: f32,
+        scale: f32,
+    ) -> Result<Self, HeightmapError> {
+        let size = image.size();
+        if size.element_product() == 0 {
+            return Err(HeightmapError::Empty);
+        }
+        if layout == HeightmapLayout::Cube && size.y != size.x * 6 {
+            return Err(HeightmapError::NotCube(size));
+        }
+        let srgb = image.texture_descriptor.format.is_srgb();
+
+        let mut samples = Vec::with_capacity(size.element_product() as usize);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let color = image.get_color_at(x, y)?;
+                // Read back the stored value, whichever color space it's tagged with.
+                samples.push(if srgb {
+                    color.to_srgba().red
+                } else {
+                    color.to_linear().red
+                });
+            }
+        }
+
+        Ok(HeightmapSurface {
+            layout,
+            base,
+            scale,
+            size,
+            samples,
+        })
+    }
+
+    fn texel(&self, x: u32, y: u32) -> f32 {
+        self.samples[(y * self.size.x + x) as usize]
+    }
+
+    /// Bilinearly samples the pixels between `min` and `max` at `uv`, wrapping
+    /// horizontally if `wrap` is set and clamping otherwise.
+    fn bilinear(&self, uv: Vec2, min: UVec2, max: UVec2, wrap: bool) -> f32 {
+        let extent = max - min;
+        let pixel = uv * extent.as_vec2() - 0.5;
+        let floor = pixel.floor();
+        let t = pixel - floor;
+
+        let column = |x: f32| {
+            let x = x as i32;
+            let x = if wrap {
+                x.rem_euclid(extent.x as i32)
+            } else {
+                x.clamp(0, extent.x as i32 - 1)
+            };
+            min.x + x as u32
+        };
+        let row = |y: f32| min.y + (y as i32).clamp(0, extent.y as i32 - 1) as u32;
+
+        let (x0, x1) = (column(floor.x), column(floor.x + 1.0));
+        let (y0, y1) = (row(floor.y), row(floor.y + 1.0));
+
+        let top = self.texel(x0, y0).lerp(self.texel(x1, y0), t.x);
+        let bottom = self.texel(x0, y1).lerp(self.texel(x1, y1), t.x);
+        top.lerp(bottom, t.y)
+    }
+}
+
+impl SurfaceHeight for HeightmapSurface {
+    fn height_at(&self, point: Vec3) -> f32 {
+        let direction = point.normalize_or(Vec3::Y);
+
+        let value = match self.layout {
+            HeightmapLayout::Equirectangular => {
+                let latitude = direction.y.clamp(-1.0, 1.0).asin();
+                let longitude = direction.x.atan2(direction.z);
+                let uv = Vec2::new(longitude / TAU + 0.5, 0.5 - latitude / PI);
+                self.bilinear(uv, UVec2::ZERO, self.size, true)
+            }
+            HeightmapLayout::Cube => {
+                let (face, uv) = cube_face(direction);
+                let side = self.size.y / 6;
+                let min = UVec2::new(0, face * side);
+                self.bilinear(uv, min, min + UVec2::new(self.size.x, side), false)
+            }
+        };
+
+        self.base + value * self.scale
+    }
+}
+
+/// The cube face `direction` points into, and where on that face.
+fn cube_face(direction: Vec3) -> (u32, Vec2) {
+    let Vec3 { x, y, z } = direction;
+    let abs = direction.abs();
+
+    let (face, major, s, t) = if abs.x >= abs.y && abs.x >= abs.z {
+        if x > 0.0 {
+            (0, abs.x, -z, -y)
+        } else {
+            (1, abs.x, z, -y)
+        }
+    } else if abs.y >= abs.z {
+        if y > 0.0 {
+            (2, abs.y, x, z)
+        } else {
+            (3, abs.y, x, -z)
+        }
+    } else if z > 0.0 {
+        (4, abs.z, x, -y)
+    } else {
+        (5, abs.z, -x, -y)
+    };
+
+    (face, (Vec2::new(s, t) / major + 1.0) * 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Image {
+        painted(width, height, |_, _| 0)
+    }
+
+    fn painted(width: u32, height: u32, pixel: impl Fn(u32, u32) -> u8) -> Image {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect();
+
+        Image::new(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::R8Unorm,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn assert_height(surface: &HeightmapSurface, direction: Vec3, expected: f32) {
+        let height = surface.height_at(direction * 3.0);
+        assert!(
+            (height - expected).abs() < 1e-3,
+            "{direction}: {height} != {expected}"
+        );
+    }
+
+    #[test]
+    fn rejects_empty_images() {
+        for layout in [HeightmapLayout::Equirectangular, HeightmapLayout::Cube] {
+            let result = HeightmapSurface::from_image(&image(0, 0), layout, 0.0, 1.0);
+            assert!(matches!(result, Err(HeightmapError::Empty)));
+        }
+    }
+
+    #[test]
+    fn rejects_cubes_of_the_wrong_shape() {
+        let result = HeightmapSurface::from_image(&image(4, 20), HeightmapLayout::Cube, 0.0, 1.0);
+        assert!(matches!(result, Err(HeightmapError::NotCube(size)) if size == UVec2::new(4, 20)));
+
+        let cube = HeightmapSurface::from_image(&image(4, 24), HeightmapLayout::Cube, 0.0, 1.0);
+        assert_eq!(cube.unwrap().height_at(Vec3::X), 0.0);
+    }
+
+    #[test]
+    fn equirectangular_has_north_at_the_top() {
+        let north = painted(8, 4, |_, y| if y == 0 { 255 } else { 0 });
+        let surface =
+            HeightmapSurface::from_image(&north, HeightmapLayout::Equirectangular, 1.0, 2.0)
+                .unwrap();
+
+        assert_height(&surface, Vec3::Y, 3.0);
+        assert_height(&surface, Vec3::NEG_Y, 1.0);
+        // Halfway between the top row and the one below it.
+        let latitude = PI / 4.0;
+        assert_height(
+            &surface,
+            Vec3::new(0.0, latitude.sin(), latitude.cos()),
+            2.0,
+        );
+    }
+
+    #[test]
+    fn equirectangular_runs_east_and_wraps() {
+        let columns = [0, 85, 170, 255];
+        let gradient = painted(4, 2, |x, _| columns[x as usize]);
+        let surface =
+            HeightmapSurface::from_image(&gradient, HeightmapLayout::Equirectangular, 0.0, 255.0)
+                .unwrap();
+
+        assert_height(&surface, Vec3::NEG_X, 42.5);
+        assert_height(&surface, Vec3::Z, 127.5);
+        assert_height(&surface, Vec3::X, 212.5);
+        // The date line blends the last column into the first.
+        assert_height(&surface, Vec3::NEG_Z, 127.5);
+        assert_height(
+            &surface,
+            Vec3::new(0.01, 0.0, -1.0),
+            127.5 + 0.01 / TAU * 4.0 * 255.0,
+        );
+    }
+
+    #[test]
+    fn cube_faces_follow_the_cube_map_layout() {
+        // Each face has its own value, plus a different amount per column and row.
+        let faces = painted(2, 12, |x, y| ((y / 2) * 40 + x * 10 + (y % 2) * 20) as u8);
+        let surface =
+            HeightmapSurface::from_image(&faces, HeightmapLayout::Cube, 0.0, 255.0).unwrap();
+
+        // Directions into the centre of the top right pixel of each face.
+        let top_right = [
+            Vec3::new(1.0, 0.5, -0.5),
+            Vec3::new(-1.0, 0.5, 0.5),
+            Vec3::new(0.5, 1.0, -0.5),
+            Vec3::new(0.5, -1.0, 0.5),
+            Vec3::new(0.5, 0.5, 1.0),
+            Vec3::new(-0.5, 0.5, -1.0),
+        ];
+        for (face, direction) in top_right.into_iter().enumerate() {
+            let value = face as f32 * 40.0;
+            let axis = Vec3::select(direction.abs().cmpeq(Vec3::ONE), direction, Vec3::ZERO);
+
+            assert_height(&surface, axis, value + 15.0);
+            assert_height(&surface, direction, value + 10.0);
+            // The opposite corner, mirrored through the face centre.
+            assert_height(&surface, axis * 2.0 - direction, value + 20.0);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{OrbitCam, OrbitCamRecorder, OrbitCamReplay, OrbitInputDelta};
+
+/// Keeps a camera moving after input stops, at the velocity of the input
+/// just before it stopped.
+///
+/// Panning and grabbing always carry on, while yaw and zoom only do so when
+/// enabled. Scroll steps are one-off impulses rather than a velocity, so they
+/// never carry on.
+#[derive(Component, Clone, Debug)]
+pub struct OrbitCamInertia {
+    /// Fraction of the velocity left after coasting for one second.
+    pub decay: f32,
+    /// How many seconds of input before release to average the velocity over.
+    pub window: f32,
+    pub yaw: bool,
+    pub zoom: bool,
+
+    frame: Option<OrbitInputDelta>,
+    held: bool,
+    samples: VecDeque<Sample>,
+    velocity: Option<Velocity>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Sample {
+    age: f32,
+    delta_secs: f32,
+    input: OrbitInputDelta,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Velocity {
+    yaw: f32,
+    zoom: f32,
+    surface: Vec3,
+    pan: Vec2,
+    shift: Vec3,
+}
+
+impl Default for OrbitCamInertia {
+    fn default() -> Self {
+        OrbitCamInertia {
+            decay: 0.05,
+            window: 0.1,
+            yaw: false,
+            zoom: false,
+            frame: None,
+            held: false,
+            samples: VecDeque::new(),
+            velocity: None,
+        }
+    }
+}
+
+impl OrbitCamInertia {
+    /// Adds input applied this frame to the release velocity.
+    pub fn sample(&mut self, input: OrbitInputDelta) {
+        let frame = self.frame.get_or_insert_with(OrbitInputDelta::default);
+        frame.yaw += input.yaw;
+        frame.pitch += input.pitch;
+        frame.zoom *= input.zoom;
+        frame.surface = input.surface * frame.surface;
+        frame.pan += input.pan;
+        frame.shift += input.shift;
+    }
+
+    /// Marks a drag or touch as still held this frame, even if it didn't move,
+    /// so coasting waits until it's released.
+    pub fn hold(&mut self) {
+        self.held = true;
+    }
+
+    /// Stops any coasting and forgets recent input.
+    pub fn stop(&mut self) {
+        self.frame = None;
+        self.held = false;
+        self.samples.clear();
+        self.velocity = None;
+    }
+
+    /// Whether the camera is currently coasting.
+    pub fn coasting(&self) -> bool {
+        self.velocity.is_some()
+    }
+
+    fn step(&mut self, delta_secs: f32) -> Option<OrbitInputDelta> {
+        for sample in self.samples.iter_mut() {
+            sample.age += delta_secs;
+        }
+        while self
+            .samples
+            .front()
+            .is_some_and(|sample| sample.age > self.window)
+        {
+            self.samples.pop_front();
+        }
+
+        // Holding still counts as input, so letting go after stopping doesn't fling.
+        let held = std::mem::take(&mut self.held);
+        if let Some(input) = self.frame.take().or(held.then(OrbitInputDelta::default)) {
+            self.samples.push_back(Sample {
+                age: 0.0,
+                delta_secs,
+                input,
+            });
+            self.velocity = None;
+            return None;
+        }
+
+        if !self.samples.is_empty() {
+            self.velocity = Some(self.release_velocity());
+            self.samples.clear();
+        }
+
+        let velocity = self.velocity.as_mut()?;
+        let delta = OrbitInputDelta {
+            yaw: if self.yaw {
+                velocity.yaw * delta_secs
+            } else {
+                0.0
+            },
+            zoom: if self.zoom {
+                (velocity.zoom * delta_secs).exp()
+            } else {
+                1.0
+            },
+            surface: Quat::from_scaled_axis(velocity.surface * delta_secs),
+            pan: velocity.pan * delta_secs,
+            shift: velocity.shift * delta_secs,
+            ..default()
+        };
+
+        let retained = self.decay.powf(delta_secs);
+        velocity.yaw *= retained;
+        velocity.zoom *= retained;
+        velocity.surface *= retained;
+        velocity.pan *= retained;
+        velocity.shift *= retained;
+
+        let speed = velocity.pan.length()
+            + velocity.surface.length()
+            + velocity.shift.length()
+            + if self.yaw { velocity.yaw.abs() } else { 0.0 }
+            + if self.zoom { velocity.zoom.abs() } else { 0.0 };
+        if speed < 1e-4 {
+            self.velocity = None;
+        }
+
+        Some(delta)
+    }
+
+    fn release_velocity(&self) -> Velocity {
+        let duration: f32 = self.samples.iter().map(|sample| sample.delta_secs).sum();
+        if duration <= 0.0 {
+            return Velocity::default();
+        }
+
+        let mut total = Velocity::default();
+        for Sample { input, .. } in &self.samples {
+            total.yaw += input.yaw;
+            total.zoom += input.zoom.ln();
+            total.surface += input.surface.to_scaled_axis();
+            total.pan += input.pan;
+            total.shift += input.shift;
+        }
+
+        Velocity {
+            yaw: total.yaw / duration,
+            zoom: total.zoom / duration,
+            surface: total.surface / duration,
+            pan: total.pan / duration,
+            shift: total.shift / duration,
+        }
+    }
+}
+
+pub fn apply_inertia(
+    mut query: Query<
+        (
+            &mut OrbitCam,
+            &mut OrbitCamInertia,
+            Option<&mut OrbitCamRecorder>,
+        ),
+        Without<OrbitCamReplay>,
+    >,
+    time: Res<Time>,
+) {
+    for (mut orbcam, mut inertia, recorder) in query.iter_mut() {
+        let Some(delta) = inertia.step(time.delta_secs()) else {
+            continue;
+        };
+
+        orbcam.apply_input(delta);
+        if let Some(mut recorder) = recorder {
+            recorder.record(delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    fn drag(inertia: &mut OrbitCamInertia) {
+        inertia.sample(OrbitInputDelta {
+            pan: Vec2::new(0.01, 0.0),
+            ..default()
+        });
+        inertia.hold();
+        assert!(inertia.step(FRAME).is_none());
+    }
+
+    #[test]
+    fn holding_still_does_not_coast() {
+        let mut inertia = OrbitCamInertia::default();
+        for _ in 0..10 {
+            drag(&mut inertia);
+        }
+
+        for _ in 0..3 {
+            inertia.hold();
+            assert!(inertia.step(FRAME).is_none());
+            assert!(!inertia.coasting());
+        }
+    }
+
+    #[test]
+    fn releasing_a_drag_coasts() {
+        let mut inertia = OrbitCamInertia::default();
+        for _ in 0..10 {
+            drag(&mut inertia);
+        }
+
+        let delta = inertia.step(FRAME).unwrap();
+        assert!(delta.pan.x > 0.0);
+        assert!(inertia.coasting());
+    }
+
+    #[test]
+    fn releasing_after_holding_still_does_not_fling() {
+        let mut inertia = OrbitCamInertia::default();
+        for _ in 0..10 {
+            drag(&mut inertia);
+        }
+        for _ in 0..12 {
+            inertia.hold();
+            inertia.step(FRAME);
+        }
+
+        let coast = inertia.step(FRAME);
+        assert!(coast.is_none_or(|delta| delta.pan.length() < 1e-6));
+    }
+}
@@ -1,177 +1,829 @@
-use std::ops::Neg;
+use std::{collections::HashMap, ops::Neg};
 
 use bevy::{
     app::Plugin,
-    input::{mouse::MouseWheel, ButtonInput},
+    ecs::reflect::ReflectMapEntities,
+    input::mouse::{MouseMotion, MouseWheel},
     math::{Quat, Vec3},
     prelude::*,
+    render::view::VisibilitySystems,
     time::Time,
 };
 use buttery::{Rotate, TransformComponent, Translate};
+use reflect::{SmoothedF32, SmoothedQuat, SmoothedVec3};
+use serde::{Deserialize, Serialize};
+
+mod action;
+mod body;
+mod bookmarks;
+mod collision;
+mod flight;
+mod focus;
+mod geodetic;
+mod heightmap;
+mod inertia;
+#[cfg(feature = "persist")]
+mod persist;
+mod rebind;
+mod reflect;
+mod replay;
+mod routing;
+mod state;
+mod surface;
+mod terrain;
+#[cfg(test)]
+mod testing;
+
+pub use action::*;
+pub use body::*;
+pub use bookmarks::*;
+pub use collision::*;
+pub use flight::*;
+pub use focus::*;
+pub use geodetic::*;
+pub use heightmap::*;
+pub use inertia::*;
+#[cfg(feature = "persist")]
+pub use persist::*;
+pub use rebind::*;
+pub use replay::*;
+pub use routing::*;
+pub use state::*;
+pub use surface::*;
+pub use terrain::*;
 
 #[derive(Default)]
 pub struct OrbitCamPlugin(OrbitCamConfig);
 
 impl Plugin for OrbitCamPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(self.0)
-            .add_systems(Update, (update_orbitcams, OrbitCam::process_input));
+        app.insert_resource(self.0.clone())
+            .init_resource::<OrbitCamInputRouting>()
+            .init_resource::<OrbitCamFocusedViewport>()
+            .init_resource::<OrbitCamRebind>()
+            .init_resource::<OrbitCamBookmarks>()
+            .register_type::<OrbitCam>()
+            .add_event::<OrbitCamRebound>()
+            .add_event::<OrbitFlightFinished>()
+            .add_systems(
+                Update,
+                (
+                    focus_viewport,
+                    capture_rebind,
+                    begin_recording_frame,
+                    OrbitCam::process_input,
+                    OrbitCam::process_touch,
+                    OrbitCam::process_gamepad,
+                    process_bookmarks,
+                    apply_inertia,
+                )
+                    .chain(),
+            )
+            // Bodies and focus entities have to be propagated first, or the
+            // camera trails a frame behind them.
+            .add_systems(
+                PostUpdate,
+                (
+                    update_orbitcams,
+                    replay_orbitcams,
+                    avoid_collisions::<AabbObstacles>,
+                    propagate_orbitcams,
+                )
+                    .chain()
+                    .after(TransformSystem::TransformPropagate)
+                    .before(VisibilitySystems::UpdateFrusta),
+            );
     }
 }
 
-#[derive(Component)]
+/// Serializes and clones through [`OrbitCamSnapshot`], as the smoothed
+/// components can't be serialized themselves. They're still reflected field
+/// by field, so inspectors can edit them.
+#[derive(Component, Reflect, Debug, Serialize, Deserialize)]
+#[reflect(Component, Default, Debug, MapEntities, Serialize, Deserialize)]
+#[serde(from = "OrbitCamSnapshot", into = "OrbitCamSnapshot")]
 pub struct OrbitCam {
+    #[reflect(remote = SmoothedQuat)]
     pub up: TransformComponent<Rotate>,
+    #[reflect(remote = SmoothedF32)]
     pub inclination: TransformComponent<Translate<f32>>,
+    #[reflect(remote = SmoothedF32)]
     pub distance: TransformComponent<Translate<f32>>,
+    #[reflect(remote = SmoothedF32)]
     pub target_height: TransformComponent<Translate<f32>>,
+    #[reflect(remote = SmoothedF32)]
     pub min: TransformComponent<Translate<f32>>,
+    pub flight: Option<OrbitFlight>,
+    pub focus: OrbitFocus,
+    /// Where the focus is, eased so switching focus doesn't jump.
+    #[reflect(remote = SmoothedVec3)]
+    pub focus_position: TransformComponent<Translate<Vec3>>,
+    pub body: Option<OrbitBody>,
+    pub surface: OrbitSurface,
+    /// Limits `inclination.target` by `distance.target`.
+    pub tilt_curve: TiltCurve,
 }
 
-#[derive(Resource, Copy, Clone)]
+/// Input settings for orbit cameras, which can be saved with `Persist` under
+/// the `persist` feature.
+///
+/// Inserted as a resource this applies to every camera, and inserted as a
+/// component on a camera it overrides the resource for that camera.
+#[derive(Resource, Component, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OrbitCamConfig {
-    pub forward: KeyCode,
-    pub left: KeyCode,
-    pub right: KeyCode,
-    pub backward: KeyCode,
+    pub actions: ActionMap,
+
+    /// Radians per second of yaw while a rotate action is held.
+    pub yaw_speed: f32,
+    /// Radians per second of inclination while a tilt action is held.
+    pub tilt_speed: f32,
+    /// Factor the distance is multiplied by per second while a zoom action is held.
+    pub zoom_speed: f32,
+    /// Radians per second of panning while a move action is held, at long distances.
+    pub pan_speed: f32,
+    /// How panning speed scales with distance.
+    pub pan_curve: PanCurve,
+
+    /// Radians of yaw and inclination per pixel dragged.
+    pub rotate_sensitivity: f32,
+    /// Radians of panning per pixel dragged, at long distances.
+    pub pan_sensitivity: f32,
+    /// Fraction the distance grows by per line scrolled, compounding over several lines.
+    pub scroll_sensitivity: f32,
+
+    pub invert_x: bool,
+    pub invert_y: bool,
+    pub invert_zoom: bool,
+
+    pub drag_pan_mode: DragPanMode,
+    pub zoom_to_cursor: bool,
+    /// How recalling a bookmark with a hotkey moves the camera.
+    pub bookmark_transition: Transition,
+
+    pub pan_stick: GamepadStick,
+    pub look_stick: GamepadStick,
+    /// Stick magnitudes below this are ignored.
+    pub stick_deadzone: f32,
+    /// Exponent applied to the stick magnitude past the deadzone, for finer control near the center.
+    pub stick_exponent: f32,
+}
 
-    pub cw: KeyCode,
-    pub ccw: KeyCode,
+#[cfg(feature = "persist")]
+impl Persist for OrbitCamConfig {}
 
-    pub tilt_up: KeyCode,
-    pub tilt_down: KeyCode,
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+impl GamepadStick {
+    pub fn read(self, gamepad: &Gamepad) -> Vec2 {
+        match self {
+            GamepadStick::Left => gamepad.left_stick(),
+            GamepadStick::Right => gamepad.right_stick(),
+        }
+    }
+}
 
-    pub zoom_in: KeyCode,
-    pub zoom_out: KeyCode,
+/// Scales panning speed by the distance of the camera.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum PanCurve {
+    /// Eases from no movement up to full speed as the distance grows.
+    #[default]
+    Sigmoid,
+    /// Full speed regardless of distance.
+    Constant,
+    /// Speed proportional to distance, by the contained factor.
+    Linear(f32),
+    /// Can't be serialized, so configs using it can't be saved.
+    #[serde(skip)]
+    Custom(fn(f32) -> f32),
+}
+
+impl PanCurve {
+    pub fn scale(&self, distance: f32) -> f32 {
+        match self {
+            PanCurve::Sigmoid => (distance.sqrt().neg().exp() + 1.0).recip() * 2.0 - 1.0,
+            PanCurve::Constant => 1.0,
+            PanCurve::Linear(factor) => distance * factor,
+            PanCurve::Custom(curve) => curve(distance),
+        }
+    }
+}
+
+/// The lowest and highest inclination allowed at a given distance, so the
+/// camera can look at the horizon up close but only straight down from orbit.
+#[derive(Copy, Clone, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(opaque, Debug, Default, PartialEq)]
+pub enum TiltCurve {
+    /// The same limits at every distance.
+    Fixed { min: f32, max: f32 },
+    /// Blends from the `(min, max)` limits at `near_distance` to those at
+    /// `far_distance`, holding them past either end. If `far_distance` isn't
+    /// past `near_distance` it steps straight to `far` at `near_distance`.
+    Lerp {
+        near_distance: f32,
+        near: (f32, f32),
+        far_distance: f32,
+        far: (f32, f32),
+    },
+    /// Can't be serialized, so cameras using it can't be saved.
+    #[serde(skip)]
+    Custom(fn(f32) -> (f32, f32)),
+}
+
+/// Custom curves compare by function address, so a curve always equals itself
+/// but the same function may not if it was instantiated more than once.
+impl PartialEq for TiltCurve {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                TiltCurve::Fixed { min, max },
+                TiltCurve::Fixed {
+                    min: other_min,
+                    max: other_max,
+                },
+            ) => min == other_min && max == other_max,
+            (
+                TiltCurve::Lerp {
+                    near_distance,
+                    near,
+                    far_distance,
+                    far,
+                },
+                TiltCurve::Lerp {
+                    near_distance: other_near_distance,
+                    near: other_near,
+                    far_distance: other_far_distance,
+                    far: other_far,
+                },
+            ) => {
+                near_distance == other_near_distance
+                    && near == other_near
+                    && far_distance == other_far_distance
+                    && far == other_far
+            }
+            (TiltCurve::Custom(curve), TiltCurve::Custom(other)) => {
+                std::ptr::fn_addr_eq(*curve, *other)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for TiltCurve {
+    fn default() -> Self {
+        TiltCurve::Fixed {
+            min: 0.0,
+            max: std::f32::consts::FRAC_PI_2,
+        }
+    }
+}
+
+impl TiltCurve {
+    pub fn limits(&self, distance: f32) -> (f32, f32) {
+        match *self {
+            TiltCurve::Fixed { min, max } => (min, max),
+            TiltCurve::Lerp {
+                near_distance,
+                near,
+                far_distance,
+                far,
+            } => {
+                let t = if far_distance > near_distance {
+                    ((distance - near_distance) / (far_distance - near_distance)).clamp(0.0, 1.0)
+                } else if distance < near_distance {
+                    0.0
+                } else {
+                    1.0
+                };
+                (near.0.lerp(far.0, t), near.1.lerp(far.1, t))
+            }
+            TiltCurve::Custom(curve) => curve(distance),
+        }
+    }
+
+    pub fn clamp(&self, inclination: f32, distance: f32) -> f32 {
+        let (min, max) = self.limits(distance);
+        // Unlike `f32::clamp` this ignores NaN limits rather than panicking.
+        inclination.max(min).min(max.max(min))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum DragPanMode {
+    /// Pans by an amount proportional to the mouse motion, scaled by distance.
+    #[default]
+    Scaled,
+    /// Keeps the surface point grabbed under the cursor fixed under the cursor.
+    Grab,
+}
+
+impl OrbitCamConfig {
+    /// Reads `stick` from `gamepad` with the deadzone and response curve applied.
+    pub fn stick(&self, gamepad: &Gamepad, stick: GamepadStick) -> Vec2 {
+        let raw = stick.read(gamepad);
+        let magnitude = raw.length();
+        if magnitude <= self.stick_deadzone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = ((magnitude - self.stick_deadzone) / (1.0 - self.stick_deadzone)).min(1.0);
+        raw / magnitude * scaled.powf(self.stick_exponent)
+    }
+
+    /// Applies the inversion settings to yaw, pitch and zoom deltas.
+    pub fn invert(&self, yaw: f32, pitch: f32, zoom: f32) -> (f32, f32, f32) {
+        let sign = |invert| if invert { -1.0 } else { 1.0 };
+        (
+            yaw * sign(self.invert_x),
+            pitch * sign(self.invert_y),
+            zoom * sign(self.invert_zoom),
+        )
+    }
 }
 
 impl Default for OrbitCamConfig {
     fn default() -> Self {
         OrbitCamConfig {
-            forward: KeyCode::KeyW,
-            left: KeyCode::KeyA,
-            right: KeyCode::KeyD,
-            backward: KeyCode::KeyS,
-            cw: KeyCode::ArrowLeft,
-            ccw: KeyCode::ArrowRight,
-            tilt_up: KeyCode::ArrowUp,
-            tilt_down: KeyCode::ArrowDown,
-            zoom_in: KeyCode::ShiftLeft,
-            zoom_out: KeyCode::Space,
+            actions: ActionMap::standard(),
+            yaw_speed: 3.0,
+            tilt_speed: 4.8,
+            zoom_speed: 10.0,
+            pan_speed: 2.4,
+            pan_curve: PanCurve::Sigmoid,
+            rotate_sensitivity: 0.005,
+            pan_sensitivity: 0.002,
+            scroll_sensitivity: 0.2,
+            invert_x: false,
+            invert_y: false,
+            invert_zoom: false,
+            drag_pan_mode: DragPanMode::Scaled,
+            zoom_to_cursor: false,
+            bookmark_transition: Transition::Smooth,
+            pan_stick: GamepadStick::Left,
+            look_stick: GamepadStick::Right,
+            stick_deadzone: 0.15,
+            stick_exponent: 2.0,
         }
     }
 }
 
-fn update_orbitcams(mut query: Query<(&mut Transform, &mut OrbitCam)>, delta: Res<Time>) {
+/// One frame's worth of input for a single camera.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OrbitInputDelta {
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Factor to multiply the distance by.
+    pub zoom: f32,
+    /// Rotation applied to `up` in world space, from grabbing or zooming to the cursor.
+    pub surface: Quat,
+    /// Radians to pan across the surface, with the pan curve already applied.
+    pub pan: Vec2,
+    /// Distance to move the focus by, from grabbing or zooming to the cursor on a plane.
+    pub shift: Vec3,
+}
+
+impl Default for OrbitInputDelta {
+    fn default() -> Self {
+        OrbitInputDelta {
+            yaw: 0.0,
+            pitch: 0.0,
+            zoom: 1.0,
+            surface: Quat::IDENTITY,
+            pan: Vec2::ZERO,
+            shift: Vec3::ZERO,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_orbitcams(
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut OrbitCam,
+            Option<&OrbitCamTerrain>,
+            Option<&mut OrbitCamRecorder>,
+            Option<&Parent>,
+        ),
+        Without<OrbitCamReplay>,
+    >,
+    transforms: Query<&GlobalTransform>,
+    mut arrivals: EventWriter<OrbitFlightFinished>,
+    terrain: Option<Res<OrbitCamTerrain>>,
+    delta: Res<Time>,
+) {
     let delta = delta.delta_secs();
 
-    for (mut transform, mut orbcam) in query.iter_mut() {
-        let new_transform = orbcam.drive(delta);
-        *transform = new_transform;
+    for (entity, mut transform, mut orbcam, camera_terrain, mut recorder, parent) in
+        query.iter_mut()
+    {
+        if let Some(recorder) = &mut recorder {
+            recorder.record_changes(&orbcam);
+        }
+
+        let body = orbcam.body_transform(&transforms);
+        orbcam.track_focus(&transforms, &body);
+
+        let flying = orbcam.flight.is_some();
+        let terrain = camera_terrain.or(terrain.as_deref());
+        let new_transform = orbcam.drive_over(delta, terrain);
+        *transform = relative_to_parent(body * new_transform, parent, &transforms);
+
+        if let Some(recorder) = &mut recorder {
+            recorder.record_drive(&orbcam, body);
+        }
+
+        if flying && orbcam.flight.is_none() {
+            arrivals.send(OrbitFlightFinished { camera: entity });
+        }
     }
 }
 
 impl OrbitCam {
     pub fn drive(&mut self, time: f32) -> Transform {
-        let up = self.up.drive(time);
-        let incl = self.inclination.drive(time);
-        let dist = self.distance.drive(time);
-        let height = self.target_height.drive(time);
-        let min = self.min.drive(time);
+        self.drive_over(time, None)
+    }
 
+    /// Like [`drive`](Self::drive), but keeping the camera above `terrain`.
+    pub fn drive_over(&mut self, time: f32, terrain: Option<&OrbitCamTerrain>) -> Transform {
+        self.advance_flight(time);
+
+        // Targets can be set directly, and zooming out can tighten the limits.
+        self.inclination.target = self
+            .tilt_curve
+            .clamp(self.inclination.target, self.distance.target);
+
+        if let Some(terrain) = terrain.filter(|terrain| terrain.track_height) {
+            let center = self.surface.place(
+                self.up.target,
+                self.target_height.target,
+                Vec3::ZERO,
+                self.min.target,
+            );
+            let point = self
+                .surface
+                .terrain_point(center, self.focus_position.target);
+            self.target_height.target = terrain.height.height_at(point);
+        }
+
+        let state = OrbitCamState {
+            up: self.up.drive(time),
+            inclination: self.inclination.drive(time),
+            distance: self.distance.drive(time),
+            target_height: self.target_height.drive(time),
+            min: self.min.drive(time),
+        };
+        let focus = self.focus_position.drive(time);
+
+        self.compose(state, focus, terrain)
+    }
+
+    /// The transform the camera is easing towards, relative to its body.
+    pub fn target_transform(&self) -> Transform {
+        self.compose(
+            self.state(OrbitCamSlot::Target),
+            self.focus_position.target,
+            None,
+        )
+    }
+
+    fn compose(
+        &self,
+        state: OrbitCamState,
+        focus: Vec3,
+        terrain: Option<&OrbitCamTerrain>,
+    ) -> Transform {
+        let OrbitCamState {
+            up,
+            inclination: incl,
+            distance: dist,
+            target_height: height,
+            min,
+        } = state;
+
+        let up = self.surface.constrain(up);
         let arm = dist * Quat::from_rotation_x(-incl).mul_vec3(Vec3::Z);
-        let mut pos = Vec3::Y * height + arm;
-        let pos_len = pos.length();
-        if pos_len < min {
-            pos.y += min - pos_len;
+        let mut pos = self.surface.place(up, height, arm, min);
+        if let Some(terrain) = terrain {
+            let center = self.surface.place(up, height, Vec3::ZERO, min);
+            let floor = terrain
+                .floor(self.surface.terrain_point(pos, focus))
+                .max(terrain.floor(self.surface.terrain_point(center, focus)));
+            if floor > min {
+                pos = self.surface.place(up, height, arm, floor);
+            }
         }
-        let pos = up * pos;
         let rotation = up * Quat::from_rotation_x(-incl);
 
         Transform {
             rotation,
-            translation: pos,
+            translation: focus + pos,
             scale: Vec3::ONE,
         }
     }
 
+    /// Casts a ray through `viewport_position` from the target transform and
+    /// returns where it hits the surface raised to `target_height`, relative
+    /// to the focus.
+    pub fn surface_hit(&self, camera: &Camera, viewport_position: Vec2) -> Option<Vec3> {
+        let view = GlobalTransform::from(self.target_transform());
+        let ray = camera.viewport_to_world(&view, viewport_position).ok()?;
+        let ray = Ray3d {
+            origin: ray.origin - self.focus_position.target,
+            ..ray
+        };
+        self.surface.hit(ray, self.target_height.target)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn process_input(
-        mut cameras: Query<&mut OrbitCam>,
-        keys: Res<ButtonInput<KeyCode>>,
+        mut cameras: Query<RoutedOrbitCam, Without<OrbitCamReplay>>,
+        mut grabs: Local<HashMap<Entity, Vec3>>,
+        input: ActionInput,
+        router: InputRouter,
         mut scroll: EventReader<MouseWheel>,
+        mut motion: EventReader<MouseMotion>,
         config: Res<OrbitCamConfig>,
+        time: Res<Time>,
     ) {
-        let (mut yaw, mut pitch) = (0.0, 0.0);
+        let delta = time.delta_secs();
+        let drag: Vec2 = motion.read().map(|event| event.delta).sum();
 
-        if keys.pressed(config.tilt_up) {
-            pitch -= 1.0;
-        }
+        let mut scroll_lines = 0.0;
 
-        if keys.pressed(config.tilt_down) {
-            pitch += 1.0;
+        for scroll_event in scroll.read() {
+            match scroll_event.unit {
+                bevy::input::mouse::MouseScrollUnit::Line => scroll_lines += scroll_event.y,
+                bevy::input::mouse::MouseScrollUnit::Pixel => scroll_lines += scroll_event.y * 0.1,
+            }
         }
 
-        if keys.pressed(config.cw) {
-            yaw += 1.0;
-        }
+        for mut routed in cameras.iter_mut() {
+            let (entity, view) = (routed.entity, routed.view);
+            let config = routed.config.unwrap_or(&config);
+            let cursor = view.and_then(|view| router.cursor(view));
 
-        if keys.pressed(config.ccw) {
-            yaw -= 1.0;
-        }
+            if !router.accepts(entity, view, routed.marked, cursor) {
+                grabs.remove(&entity);
+                continue;
+            }
 
-        yaw *= 0.05;
-        pitch *= 0.08;
+            let actions = &config.actions;
+            let value = |action| input.value(actions, action);
 
-        let mut delta_zoom = 0.0;
+            let mut yaw = value(OrbitAction::Cw) - value(OrbitAction::Ccw);
+            let mut pitch = value(OrbitAction::TiltDown) - value(OrbitAction::TiltUp);
 
-        for scroll_event in scroll.read() {
-            match scroll_event.unit {
-                bevy::input::mouse::MouseScrollUnit::Line => delta_zoom += scroll_event.y,
-                bevy::input::mouse::MouseScrollUnit::Pixel => delta_zoom += scroll_event.y * 0.1,
+            yaw *= config.yaw_speed * delta;
+            pitch *= config.tilt_speed * delta;
+
+            let key_zoom = value(OrbitAction::ZoomOut) - value(OrbitAction::ZoomIn);
+
+            let mut right = value(OrbitAction::Left) - value(OrbitAction::Right);
+            let mut up = value(OrbitAction::Backward) - value(OrbitAction::Forward);
+
+            right *= config.pan_speed * delta;
+            up *= config.pan_speed * delta;
+
+            if input.pressed(actions, OrbitAction::DragRotate) {
+                yaw += drag.x * config.rotate_sensitivity;
+                pitch += drag.y * config.rotate_sensitivity;
             }
-        }
 
-        if keys.pressed(config.zoom_out) {
-            delta_zoom += 0.2;
-        } else if keys.pressed(config.zoom_in) {
-            delta_zoom -= 0.2;
-        }
+            let (yaw, pitch, zoom_sign) = config.invert(yaw, pitch, 1.0);
+            let scroll_zoom = scroll_lines * zoom_sign;
+            let key_zoom = key_zoom * zoom_sign;
 
-        let (mut up, mut right) = (0.0, 0.0);
+            let zoom = config.zoom_speed.powf(key_zoom * delta);
+            // Multiplicative like the keys, so no amount of scrolling can
+            // take the distance to zero or past it.
+            let scroll_zoom_factor = (1.0 + config.scroll_sensitivity).powf(scroll_zoom);
 
-        if keys.pressed(config.right) {
-            right -= 1.0;
-        }
+            let drag_pan = input.pressed(actions, OrbitAction::DragPan);
+
+            if drag_pan && config.drag_pan_mode == DragPanMode::Scaled {
+                right += drag.x * config.pan_sensitivity;
+                up -= drag.y * config.pan_sensitivity;
+            }
+
+            let camera = &routed.orbit;
+            let hit = view.zip(cursor).and_then(|(view, cursor)| {
+                let offset = view
+                    .logical_viewport_rect()
+                    .map_or(Vec2::ZERO, |rect| rect.min);
+                camera.surface_hit(view, cursor - offset)
+            });
+
+            let mut surface = Quat::IDENTITY;
+            let mut shift = Vec3::ZERO;
+            let focus = camera.focus_position.target;
+            let height = camera.target_height.target;
+
+            if drag_pan && config.drag_pan_mode == DragPanMode::Grab {
+                if let Some(hit) = hit {
+                    let anchor = *grabs
+                        .entry(entity)
+                        .or_insert_with(|| camera.surface.grab_anchor(hit, focus));
+                    (surface, shift) = camera.surface.grab(anchor, hit, focus, height);
+                }
+            } else {
+                grabs.remove(&entity);
+            }
+
+            // Scrolling comes in single steps, so it's applied separately to
+            // keep it from being carried on by inertia.
+            let mut scroll_step = OrbitInputDelta {
+                zoom: scroll_zoom_factor,
+                ..default()
+            };
+
+            if let Some(hit) = hit.filter(|_| config.zoom_to_cursor && scroll_zoom != 0.0) {
+                // Move the view center towards the hit by the same fraction the
+                // distance shrinks by, so zooming out moves away from it instead.
+                let fraction = (1.0 - scroll_zoom_factor).clamp(-1.0, 1.0);
+                match camera.surface {
+                    OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => {
+                        let center = surface * camera.up.target * Vec3::Y;
+                        let normal = camera.surface.normal_at(hit, height);
+                        let (axis, angle) = Quat::from_rotation_arc(center, normal).to_axis_angle();
+                        scroll_step.surface = Quat::from_axis_angle(axis, angle * fraction);
+                    }
+                    OrbitSurface::Plane => {
+                        scroll_step.shift = (hit - shift).with_y(0.0) * fraction;
+                    }
+                }
+            }
 
-        if keys.pressed(config.left) {
-            right += 1.0;
+            let pan = Vec2::new(right, up) * config.pan_curve.scale(camera.distance.current);
+
+            routed.apply_input(OrbitInputDelta {
+                yaw,
+                pitch,
+                zoom,
+                surface,
+                pan,
+                shift,
+            });
+            routed.apply_impulse(scroll_step);
+            if drag_pan || input.pressed(actions, OrbitAction::DragRotate) {
+                routed.hold_input();
+            }
         }
+    }
+
+    pub fn process_touch(
+        mut cameras: Query<RoutedOrbitCam, Without<OrbitCamReplay>>,
+        touches: Res<Touches>,
+        mut events: EventReader<TouchInput>,
+        router: InputRouter,
+        config: Res<OrbitCamConfig>,
+    ) {
+        // `Touches` only moves on previous positions when there are events, so
+        // a finger held still would otherwise repeat its last movement.
+        let moved = !events.is_empty();
+        events.clear();
+
+        let mut active = touches.iter().collect::<Vec<_>>();
+        active.sort_by_key(|touch| touch.id());
 
-        if keys.pressed(config.forward) {
-            up -= 1.0;
+        let pointer = active.first().map(|touch| touch.position());
+
+        for mut routed in cameras.iter_mut() {
+            if !router.accepts(routed.entity, routed.view, routed.marked, pointer) {
+                continue;
+            }
+
+            let config = routed.config.unwrap_or(&config);
+            let scale = config.pan_curve.scale(routed.orbit.distance.current);
+
+            let delta = match active[..] {
+                [] => continue,
+                _ if !moved => OrbitInputDelta::default(),
+                [touch] => OrbitInputDelta {
+                    pan: touch.delta() * Vec2::new(1.0, -1.0) * config.pan_sensitivity * scale,
+                    ..default()
+                },
+                [first, second, ..] => {
+                    let span = second.position() - first.position();
+                    let previous_span = second.previous_position() - first.previous_position();
+
+                    let zoom = (previous_span.length() / span.length()).ln();
+                    let twist = previous_span.angle_to(span);
+                    let drag = (first.delta().y + second.delta().y) * 0.5;
+
+                    let (twist, pitch, zoom) =
+                        config.invert(twist, drag * config.rotate_sensitivity, zoom);
+
+                    OrbitInputDelta {
+                        yaw: if twist.is_finite() { twist } else { 0.0 },
+                        pitch,
+                        zoom: if zoom.is_finite() { zoom.exp() } else { 1.0 },
+                        ..default()
+                    }
+                }
+            };
+
+            routed.apply_input(delta);
+            routed.hold_input();
         }
+    }
+
+    pub fn process_gamepad(
+        mut cameras: Query<RoutedOrbitCam, Without<OrbitCamReplay>>,
+        gamepads: Query<&Gamepad>,
+        router: InputRouter,
+        config: Res<OrbitCamConfig>,
+        time: Res<Time>,
+    ) {
+        let delta = time.delta_secs();
+
+        for mut routed in cameras.iter_mut() {
+            let cursor = routed.view.and_then(|view| router.cursor(view));
+            if !router.accepts(routed.entity, routed.view, routed.marked, cursor) {
+                continue;
+            }
+
+            let config = routed.config.unwrap_or(&config);
+            let (mut pan, mut look) = (Vec2::ZERO, Vec2::ZERO);
 
-        if keys.pressed(config.backward) {
-            up += 1.0;
+            for gamepad in gamepads.iter() {
+                pan += config.stick(gamepad, config.pan_stick);
+                look += config.stick(gamepad, config.look_stick);
+            }
+
+            let (yaw, pitch, _) = config.invert(
+                -look.x * config.yaw_speed * delta,
+                -look.y * config.tilt_speed * delta,
+                0.0,
+            );
+
+            let scale = config.pan_curve.scale(routed.orbit.distance.current);
+
+            routed.apply_input(OrbitInputDelta {
+                yaw,
+                pitch,
+                pan: -pan * config.pan_speed * delta * scale,
+                ..default()
+            });
         }
+    }
 
-        for mut camera in cameras.iter_mut() {
-            camera.distance.target *= 1.0 + delta_zoom * 0.2;
-            camera.up.target = (camera.up.target * Quat::from_rotation_y(yaw)).normalize();
-            camera.inclination.target =
-                (camera.inclination.target + pitch).clamp(0.0, std::f32::consts::FRAC_PI_2);
+    /// Moves `up.target` across the surface by `right` and `up` radians,
+    /// scaled by `curve` at the current distance.
+    pub fn pan(&mut self, right: f32, up: f32, curve: PanCurve) {
+        let scale = curve.scale(self.distance.current);
+        self.apply_input(OrbitInputDelta {
+            pan: Vec2::new(right, up) * scale,
+            ..default()
+        });
+    }
 
-            let want_distance = camera.distance.current;
+    pub fn apply_input(&mut self, delta: OrbitInputDelta) {
+        if delta == OrbitInputDelta::default() {
+            return;
+        }
 
-            let speed_scl = 0.04 * ((want_distance.sqrt().neg().exp() + 1.0).recip() * 2.0 - 1.0);
+        // Planes move the focus rather than rotating across the surface.
+        if self.surface != OrbitSurface::Plane {
+            self.up.target = (delta.surface * self.up.target).normalize();
+        }
+        self.distance.target *= delta.zoom;
+        // Yaw half before panning and half after, so panning while turning
+        // follows the same arc whatever the frame rate.
+        let half_yaw = Quat::from_rotation_y(delta.yaw * 0.5);
+        self.up.target = (self.up.target * half_yaw).normalize();
+        self.up.target = self.surface.constrain(self.up.target);
+        self.inclination.target = self
+            .tilt_curve
+            .clamp(self.inclination.target + delta.pitch, self.distance.target);
 
-            let axis = Vec3::Y.cross(Vec3::new(right * -speed_scl, 0.0, up * speed_scl));
-            let len = axis.length();
-            if len == 0.0 {
-                return;
+        let mut shift = delta.shift;
+        let axis = Vec3::Y.cross(Vec3::new(-delta.pan.x, 0.0, delta.pan.y));
+        if axis != Vec3::ZERO {
+            match self.surface {
+                OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => {
+                    self.up.target = (self.up.target * Quat::from_scaled_axis(axis)).normalize();
+                }
+                // Pans as far as it would across a sphere the size of the distance.
+                OrbitSurface::Plane => {
+                    let pan = Vec3::new(-delta.pan.x, 0.0, delta.pan.y);
+                    shift += self.up.target * pan * self.distance.target;
+                }
             }
+        }
+        self.up.target = (self.up.target * half_yaw).normalize();
+        self.up.target = self.surface.constrain(self.up.target);
 
-            camera.up.target = (camera.up.target * Quat::from_scaled_axis(axis)).normalize();
+        // Moving the focus by hand stops it following an entity.
+        if shift != Vec3::ZERO {
+            let focus = self.focus_position.target + shift;
+            self.focus = OrbitFocus::Point(focus);
+            self.focus_position.target = focus;
         }
     }
 
@@ -182,6 +834,12 @@ impl OrbitCam {
             distance: TransformComponent::new_zoom(4.0),
             target_height: TransformComponent::new(0.01, radius),
             min: TransformComponent::new(0.01, radius),
+            flight: None,
+            focus: OrbitFocus::default(),
+            focus_position: TransformComponent::new_translate(Vec3::ZERO),
+            body: None,
+            surface: OrbitSurface::Sphere,
+            tilt_curve: TiltCurve::default(),
         }
     }
 }
@@ -194,6 +852,601 @@ impl Default for OrbitCam {
             distance: TransformComponent::new_zoom(4.0),
             target_height: TransformComponent::new(0.01, 1.0),
             min: TransformComponent::new(0.01, 1.0),
+            flight: None,
+            focus: OrbitFocus::default(),
+            focus_position: TransformComponent::new_translate(Vec3::ZERO),
+            body: None,
+            surface: OrbitSurface::Sphere,
+            tilt_curve: TiltCurve::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        ecs::{entity::EntityHashMap, system::RunSystemOnce},
+        input::{
+            gamepad::GamepadInput,
+            touch::{touch_screen_input_system, TouchPhase},
+        },
+        scene::{serde::SceneDeserializer, DynamicSceneBuilder},
+    };
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+    use crate::testing::{input_world, spawn_view, spawn_window};
+
+    fn hold_for_one_second(frames: u32) -> OrbitCam {
+        let mut world = input_world(OrbitCamConfig {
+            tilt_speed: 1.0,
+            ..default()
+        });
+
+        let mut keys = ButtonInput::<KeyCode>::default();
+        for key in [
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowDown,
+            KeyCode::Space,
+            KeyCode::KeyW,
+        ] {
+            keys.press(key);
+        }
+        world.insert_resource(keys);
+
+        let camera = world.spawn(OrbitCam::default()).id();
+        let step = Duration::from_secs_f64(1.0 / frames as f64);
+
+        for _ in 0..frames {
+            world.resource_mut::<Time>().advance_by(step);
+            world.run_system_once(OrbitCam::process_input).unwrap();
+            let mut orbcam = world.get_mut::<OrbitCam>(camera).unwrap();
+            orbcam.drive(step.as_secs_f32());
+        }
+
+        world.entity_mut(camera).take::<OrbitCam>().unwrap()
+    }
+
+    #[test]
+    fn held_actions_move_the_same_at_any_frame_rate() {
+        let slow = hold_for_one_second(60);
+        let fast = hold_for_one_second(144);
+
+        assert!(slow.up.target.angle_between(fast.up.target) < 1e-3);
+        assert!((slow.distance.target - fast.distance.target).abs() < 1e-3);
+        assert!((slow.inclination.target - fast.inclination.target).abs() < 1e-4);
+        assert!((slow.inclination.target - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn a_scroll_step_does_not_fling() {
+        let mut world = input_world(OrbitCamConfig::default());
+        let mut inertia = OrbitCamInertia::default();
+        inertia.zoom = true;
+        let camera = world.spawn((OrbitCam::default(), inertia)).id();
+        world.send_event(MouseWheel {
+            unit: bevy::input::mouse::MouseScrollUnit::Line,
+            x: 0.0,
+            y: -1.0,
+            window: Entity::PLACEHOLDER,
+        });
+
+        // A schedule keeps the event reader's place, so the step is only read once.
+        let mut schedule = Schedule::default();
+        schedule.add_systems((OrbitCam::process_input, apply_inertia).chain());
+        for _ in 0..10 {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            schedule.run(&mut world);
+        }
+
+        let orbcam = world.get::<OrbitCam>(camera).unwrap();
+        assert!((orbcam.distance.target - 4.0 / 1.2).abs() < 1e-5);
+        assert!(!world.get::<OrbitCamInertia>(camera).unwrap().coasting());
+    }
+
+    #[test]
+    fn replays_recorded_input_and_changes() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.init_resource::<Events<OrbitFlightFinished>>();
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::ArrowLeft);
+        keys.press(KeyCode::KeyW);
+        world.insert_resource(keys);
+
+        let target = world.spawn(GlobalTransform::from_xyz(0.0, 0.0, 1.0)).id();
+        let camera = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam {
+                    focus: OrbitFocus::Entity(target),
+                    ..default()
+                },
+                OrbitCamRecorder::default(),
+            ))
+            .id();
+
+        let mut record = Schedule::default();
+        record.add_systems(
+            (
+                begin_recording_frame,
+                OrbitCam::process_input,
+                update_orbitcams,
+            )
+                .chain(),
+        );
+        let mut recorded = Vec::new();
+        for frame in 0..20 {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            *world.get_mut::<GlobalTransform>(target).unwrap() =
+                GlobalTransform::from_xyz(frame as f32 * 0.1, 0.0, 1.0);
+
+            let mut orbcam = world.get_mut::<OrbitCam>(camera).unwrap();
+            match frame {
+                5 => orbcam.fly_to(0.5, 1.0, 6.0, 0.0, 0.3, 0.1),
+                12 => {
+                    let state = orbcam.state(OrbitCamSlot::Target);
+                    orbcam.snap_to(OrbitCamState {
+                        distance: 2.0,
+                        ..state
+                    });
+                }
+                15 => orbcam.set_lat_lon(-0.4, 2.0, OrbitCamSlot::Target),
+                _ => {}
+            }
+
+            record.run(&mut world);
+            recorded.push(*world.get::<Transform>(camera).unwrap());
+        }
+
+        let log = world
+            .entity_mut(camera)
+            .take::<OrbitCamRecorder>()
+            .unwrap()
+            .log;
+        world.despawn(camera);
+        // The target has moved on, so replays can't read where it was.
+        *world.get_mut::<GlobalTransform>(target).unwrap() = GlobalTransform::IDENTITY;
+        let replayed = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam::default(),
+                OrbitCamReplay::new(log),
+            ))
+            .id();
+
+        let mut replay = Schedule::default();
+        replay.add_systems(replay_orbitcams);
+        for expected in recorded {
+            replay.run(&mut world);
+            assert_eq!(*world.get::<Transform>(replayed).unwrap(), expected);
+        }
+    }
+
+    struct TouchScreen {
+        world: World,
+        schedule: Schedule,
+        camera: Entity,
+    }
+
+    impl TouchScreen {
+        fn new() -> Self {
+            let mut world = input_world(OrbitCamConfig::default());
+            world.init_resource::<Touches>();
+            world.init_resource::<Events<TouchInput>>();
+            let camera = world.spawn(OrbitCam::default()).id();
+
+            let mut schedule = Schedule::default();
+            schedule.add_systems((touch_screen_input_system, OrbitCam::process_touch).chain());
+
+            TouchScreen {
+                world,
+                schedule,
+                camera,
+            }
+        }
+
+        /// Sends a touch event for each finger's position and runs a frame.
+        fn frame(&mut self, phase: TouchPhase, fingers: &[Vec2]) -> &OrbitCam {
+            for (id, position) in fingers.iter().enumerate() {
+                self.world.send_event(TouchInput {
+                    phase,
+                    position: *position,
+                    window: Entity::PLACEHOLDER,
+                    force: None,
+                    id: id as u64,
+                });
+            }
+            self.schedule.run(&mut self.world);
+            self.world.get::<OrbitCam>(self.camera).unwrap()
+        }
+
+        fn gesture(from: &[Vec2], to: &[Vec2]) -> OrbitCam {
+            let mut screen = TouchScreen::new();
+            screen.frame(TouchPhase::Started, from);
+            screen.frame(TouchPhase::Moved, to).clone()
+        }
+    }
+
+    #[test]
+    fn one_finger_pans() {
+        let orbcam = TouchScreen::gesture(&[Vec2::new(100.0, 100.0)], &[Vec2::new(120.0, 90.0)]);
+
+        let config = OrbitCamConfig::default();
+        let scale = config.pan_curve.scale(4.0);
+        let mut expected = OrbitCam::default();
+        expected.apply_input(OrbitInputDelta {
+            pan: Vec2::new(20.0, 10.0) * config.pan_sensitivity * scale,
+            ..default()
+        });
+
+        assert_ne!(orbcam.up.target, Quat::IDENTITY);
+        assert!(orbcam.up.target.angle_between(expected.up.target) < 1e-6);
+        assert_eq!(orbcam.distance.target, 4.0);
+        assert_eq!(orbcam.inclination.target, 0.0);
+    }
+
+    #[test]
+    fn a_finger_held_still_stops_panning() {
+        let mut screen = TouchScreen::new();
+        screen.frame(TouchPhase::Started, &[Vec2::new(100.0, 100.0)]);
+        let moved = screen
+            .frame(TouchPhase::Moved, &[Vec2::new(120.0, 100.0)])
+            .up
+            .target;
+        let held = screen.frame(TouchPhase::Moved, &[]).up.target;
+        assert_eq!(held, moved);
+    }
+
+    #[test]
+    fn pinching_zooms() {
+        let orbcam = TouchScreen::gesture(
+            &[Vec2::new(100.0, 100.0), Vec2::new(200.0, 100.0)],
+            &[Vec2::new(50.0, 100.0), Vec2::new(250.0, 100.0)],
+        );
+        assert!((orbcam.distance.target - 2.0).abs() < 1e-5);
+        assert!(orbcam.up.target.angle_between(Quat::IDENTITY) < 1e-6);
+        assert_eq!(orbcam.inclination.target, 0.0);
+    }
+
+    #[test]
+    fn twisting_yaws() {
+        let orbcam = TouchScreen::gesture(
+            &[Vec2::new(100.0, 100.0), Vec2::new(200.0, 100.0)],
+            &[Vec2::new(150.0, 50.0), Vec2::new(150.0, 150.0)],
+        );
+        let quarter = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        assert!(orbcam.up.target.angle_between(quarter) < 1e-5);
+        assert!((orbcam.distance.target - 4.0).abs() < 1e-5);
+        assert_eq!(orbcam.inclination.target, 0.0);
+    }
+
+    #[test]
+    fn dragging_two_fingers_tilts() {
+        let orbcam = TouchScreen::gesture(
+            &[Vec2::new(100.0, 100.0), Vec2::new(200.0, 100.0)],
+            &[Vec2::new(100.0, 120.0), Vec2::new(200.0, 120.0)],
+        );
+        let expected = 20.0 * OrbitCamConfig::default().rotate_sensitivity;
+        assert!((orbcam.inclination.target - expected).abs() < 1e-6);
+        assert!(orbcam.up.target.angle_between(Quat::IDENTITY) < 1e-6);
+        assert_eq!(orbcam.distance.target, 4.0);
+    }
+
+    #[test]
+    fn touching_fingers_stay_finite() {
+        let apart = TouchScreen::gesture(
+            &[Vec2::new(100.0, 100.0), Vec2::new(100.0, 100.0)],
+            &[Vec2::new(100.0, 100.0), Vec2::new(110.0, 100.0)],
+        );
+        let together = TouchScreen::gesture(
+            &[Vec2::new(100.0, 100.0), Vec2::new(110.0, 100.0)],
+            &[Vec2::new(100.0, 100.0), Vec2::new(100.0, 100.0)],
+        );
+
+        for orbcam in [apart, together] {
+            assert!(orbcam.up.target.is_finite());
+            assert!(orbcam.inclination.target.is_finite());
+            assert_eq!(orbcam.distance.target, 4.0);
+        }
+    }
+
+    fn gamepad(inputs: &[(GamepadInput, f32)]) -> Gamepad {
+        let mut gamepad = Gamepad::default();
+        for (input, value) in inputs {
+            gamepad.analog_mut().set(*input, *value);
+        }
+        gamepad
+    }
+
+    /// Runs a tenth of a second of input from `gamepad` and `keys`.
+    fn gamepad_frame(gamepad: Gamepad, keys: &[KeyCode]) -> OrbitCam {
+        let mut world = input_world(OrbitCamConfig::default());
+        let mut pressed = ButtonInput::<KeyCode>::default();
+        for key in keys {
+            pressed.press(*key);
+        }
+        world.insert_resource(pressed);
+        world.spawn(gamepad);
+        let mut orbcam = OrbitCam::default();
+        orbcam.inclination.hard_set(0.5);
+        let camera = world.spawn(orbcam).id();
+
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(0.1));
+        world.run_system_once(OrbitCam::process_input).unwrap();
+        world.run_system_once(OrbitCam::process_gamepad).unwrap();
+        world.entity_mut(camera).take::<OrbitCam>().unwrap()
+    }
+
+    #[test]
+    fn sticks_have_a_deadzone_and_curve() {
+        let config = OrbitCamConfig::default();
+        let left = |x, y| {
+            let gamepad = gamepad(&[
+                (GamepadAxis::LeftStickX.into(), x),
+                (GamepadAxis::LeftStickY.into(), y),
+            ]);
+            config.stick(&gamepad, GamepadStick::Left)
+        };
+
+        assert_eq!(left(0.1, 0.0), Vec2::ZERO);
+        assert_eq!(left(0.0, -0.15), Vec2::ZERO);
+        // Halfway between the deadzone and the edge, squared.
+        assert!(left(0.575, 0.0).abs_diff_eq(Vec2::new(0.25, 0.0), 1e-5));
+        assert!(left(0.0, -0.575).abs_diff_eq(Vec2::new(0.0, -0.25), 1e-5));
+        assert!(left(0.6, 0.8).abs_diff_eq(Vec2::new(0.6, 0.8), 1e-5));
+    }
+
+    #[test]
+    fn sticks_turn_the_same_way_as_keys() {
+        for (axis, value, key) in [
+            (GamepadAxis::RightStickX, 1.0, KeyCode::ArrowRight),
+            (GamepadAxis::RightStickX, -1.0, KeyCode::ArrowLeft),
+            (GamepadAxis::RightStickY, 1.0, KeyCode::ArrowUp),
+            (GamepadAxis::RightStickY, -1.0, KeyCode::ArrowDown),
+            (GamepadAxis::LeftStickX, 1.0, KeyCode::KeyD),
+            (GamepadAxis::LeftStickX, -1.0, KeyCode::KeyA),
+            (GamepadAxis::LeftStickY, 1.0, KeyCode::KeyW),
+            (GamepadAxis::LeftStickY, -1.0, KeyCode::KeyS),
+        ] {
+            let stick = gamepad_frame(gamepad(&[(axis.into(), value)]), &[]);
+            let keys = gamepad_frame(Gamepad::default(), &[key]);
+
+            // Every binding moves the camera, so these can't match by doing nothing.
+            assert!(stick.up.target != Quat::IDENTITY || stick.inclination.target != 0.5);
+            assert!(stick.up.target.angle_between(keys.up.target) < 1e-5);
+            assert!((stick.inclination.target - keys.inclination.target).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn triggers_zoom_through_the_action_map() {
+        let config = OrbitCamConfig::default();
+        let zoom_in = gamepad_frame(gamepad(&[(GamepadButton::RightTrigger2.into(), 0.5)]), &[]);
+        let expected = 4.0 * config.zoom_speed.powf(-0.5 * 0.1);
+        assert!((zoom_in.distance.target - expected).abs() < 1e-5);
+
+        let zoom_out = gamepad_frame(gamepad(&[(GamepadButton::LeftTrigger2.into(), 1.0)]), &[]);
+        let expected = 4.0 * config.zoom_speed.powf(0.1);
+        assert!((zoom_out.distance.target - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn wasd_moves_the_focus_across_a_plane() {
+        for (key, direction) in [
+            (KeyCode::KeyW, Vec3::NEG_Z),
+            (KeyCode::KeyS, Vec3::Z),
+            (KeyCode::KeyA, Vec3::NEG_X),
+            (KeyCode::KeyD, Vec3::X),
+        ] {
+            let mut world = input_world(OrbitCamConfig::default());
+            let mut keys = ButtonInput::<KeyCode>::default();
+            keys.press(key);
+            keys.press(KeyCode::ArrowLeft);
+            world.insert_resource(keys);
+            let yaw = Quat::from_rotation_y(0.6);
+            let mut orbcam = OrbitCam {
+                surface: OrbitSurface::Plane,
+                ..default()
+            };
+            orbcam.up.hard_set(yaw);
+            let camera = world.spawn(orbcam).id();
+
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(0.1));
+            world.run_system_once(OrbitCam::process_input).unwrap();
+
+            let orbcam = world.get::<OrbitCam>(camera).unwrap();
+            let moved = orbcam.focus_position.target;
+            assert_eq!(moved.y, 0.0);
+            // Half the yaw is applied before the move.
+            let heading = (yaw * Quat::from_rotation_y(0.15)) * direction;
+            assert!(moved.normalize().abs_diff_eq(heading, 1e-4));
+            assert_eq!(orbcam.focus, OrbitFocus::Point(moved));
+            assert!((orbcam.up.target * Vec3::Y).abs_diff_eq(Vec3::Y, 1e-6));
+        }
+    }
+
+    #[test]
+    fn large_scrolls_keep_the_distance_positive() {
+        let mut world = input_world(OrbitCamConfig::default());
+        let camera = world.spawn(OrbitCam::default()).id();
+        world.send_event(MouseWheel {
+            unit: bevy::input::mouse::MouseScrollUnit::Pixel,
+            x: 0.0,
+            y: -60.0,
+            window: Entity::PLACEHOLDER,
+        });
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(0.1));
+        world.run_system_once(OrbitCam::process_input).unwrap();
+
+        let mut orbcam = world.entity_mut(camera).take::<OrbitCam>().unwrap();
+        assert!((orbcam.distance.target - 4.0 * 1.2f32.powf(-6.0)).abs() < 1e-5);
+        for _ in 0..30 {
+            orbcam.drive(0.1);
+        }
+        orbcam.pan(0.1, 0.1, PanCurve::Sigmoid);
+        assert!(orbcam.distance.current > 0.0);
+        assert!(orbcam.up.target.is_finite());
+    }
+
+    /// Scrolls `lines` with the cursor off to the side of `orbcam`'s view,
+    /// returning where the cursor was over the surface and the camera after.
+    fn scroll_at_cursor(orbcam: &OrbitCam, lines: f32) -> (Vec3, OrbitCam) {
+        let mut world = input_world(OrbitCamConfig {
+            zoom_to_cursor: true,
+            ..default()
+        });
+        let cursor = Vec2::new(500.0, 380.0);
+        spawn_window(&mut world, cursor);
+        let camera = spawn_view(&mut world, Camera::default(), orbcam.clone());
+        let view = world.get::<Camera>(camera).unwrap();
+        let hit = orbcam.surface_hit(view, cursor).unwrap();
+
+        world.send_event(MouseWheel {
+            unit: bevy::input::mouse::MouseScrollUnit::Line,
+            x: 0.0,
+            y: lines,
+            window: Entity::PLACEHOLDER,
+        });
+        world.run_system_once(OrbitCam::process_input).unwrap();
+        (hit, world.entity_mut(camera).take::<OrbitCam>().unwrap())
+    }
+
+    #[test]
+    fn zooming_to_the_cursor_moves_by_the_zoom_fraction() {
+        let mut orbcam = OrbitCam::default();
+        orbcam.inclination.hard_set(0.6);
+        let center = orbcam.up.target * Vec3::Y;
+
+        for lines in [-1.0, 1.0] {
+            let (hit, zoomed) = scroll_at_cursor(&orbcam, lines);
+            let fraction = 1.0 - zoomed.distance.target / orbcam.distance.target;
+            let to_hit = center.angle_between(hit);
+            let moved = zoomed.up.target * Vec3::Y;
+
+            // In towards the hit when zooming in, and out away from it.
+            assert!((moved.angle_between(hit) - to_hit * (1.0 - fraction)).abs() < 1e-4);
+            assert!((moved.angle_between(center) - to_hit * fraction.abs()).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn zooming_to_the_cursor_moves_the_focus_across_planes() {
+        let mut orbcam = OrbitCam {
+            surface: OrbitSurface::Plane,
+            focus: OrbitFocus::Point(Vec3::new(2.0, 0.0, -1.0)),
+            ..default()
+        };
+        orbcam.focus_position.hard_set(Vec3::new(2.0, 0.0, -1.0));
+        orbcam.inclination.hard_set(0.9);
+        orbcam.target_height.hard_set(0.0);
+
+        let (hit, zoomed) = scroll_at_cursor(&orbcam, -1.0);
+        let fraction = 1.0 - zoomed.distance.target / orbcam.distance.target;
+        assert!(fraction > 0.0);
+
+        let moved = zoomed.focus_position.target - orbcam.focus_position.target;
+        assert!(moved.abs_diff_eq(hit.with_y(0.0) * fraction, 1e-4));
+    }
+
+    #[test]
+    fn scenes_remap_the_focus_entity() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<OrbitCam>();
+        registry.write().register::<Transform>();
+
+        let mut world = World::new();
+        world.insert_resource(registry.clone());
+        let target = world.spawn(Transform::from_xyz(1.0, 2.0, 3.0)).id();
+        let mut orbcam = OrbitCam {
+            focus: OrbitFocus::Entity(target),
+            surface: OrbitSurface::Ellipsoid {
+                equatorial: 10.0,
+                polar: 7.0,
+            },
+            tilt_curve: TiltCurve::Fixed { min: 0.1, max: 1.2 },
+            ..default()
+        };
+        // Mid-ease, so the current values differ from the targets.
+        orbcam.up.target = Quat::from_rotation_z(0.4);
+        orbcam.distance.target = 9.0;
+        orbcam.inclination.hard_set(0.3);
+        orbcam.focus_position.current = Vec3::X;
+        orbcam.distance.retention = 0.2;
+        let original = OrbitCamSnapshot::from(&orbcam);
+        let camera = world.spawn(orbcam).id();
+
+        let scene = DynamicSceneBuilder::from_world(&world)
+            .extract_entities([target, camera].into_iter())
+            .build();
+        let ron = scene.serialize(&registry.read()).unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&ron).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
         }
+        .deserialize(&mut deserializer)
+        .unwrap();
+
+        let mut loaded = World::new();
+        loaded.insert_resource(registry);
+        // Take up the original ids, so the scene has to be given new ones.
+        loaded.spawn_batch([(), ()]);
+        let mut entities = EntityHashMap::default();
+        scene.write_to_world(&mut loaded, &mut entities).unwrap();
+
+        let snapshot = OrbitCamSnapshot::from(loaded.get::<OrbitCam>(entities[&camera]).unwrap());
+        assert_ne!(entities[&target], target);
+        assert_eq!(snapshot.focus, OrbitFocus::Entity(entities[&target]));
+        // Everything else comes through as it was.
+        assert_eq!(
+            snapshot,
+            OrbitCamSnapshot {
+                focus: snapshot.focus,
+                ..original
+            }
+        );
+    }
+
+    #[test]
+    fn tilt_curves_step_and_ignore_nan() {
+        let step = TiltCurve::Lerp {
+            near_distance: 10.0,
+            near: (0.0, 1.5),
+            far_distance: 10.0,
+            far: (0.0, 0.5),
+        };
+        assert_eq!(step.clamp(1.0, 9.0), 1.0);
+        assert_eq!(step.clamp(1.0, 10.0), 0.5);
+        assert_eq!(step.clamp(1.0, 11.0), 0.5);
+
+        let nan = TiltCurve::Custom(|_| (f32::NAN, f32::NAN));
+        assert_eq!(nan.clamp(1.0, 10.0), 1.0);
+        let mut orbcam = OrbitCam {
+            tilt_curve: step,
+            ..default()
+        };
+        orbcam.distance.hard_set(10.0);
+        orbcam.inclination.hard_set(1.0);
+        orbcam.drive(0.1);
+        assert_eq!(orbcam.inclination.target, 0.5);
+    }
+
+    #[test]
+    fn custom_tilt_curves_equal_themselves() {
+        let curve = TiltCurve::Custom(|_| (0.0, 1.0));
+        assert_eq!(curve, curve);
+        assert_ne!(curve, TiltCurve::Custom(|_| (0.0, 2.0)));
+        assert_eq!(TiltCurve::default(), TiltCurve::default());
     }
 }
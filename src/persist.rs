@@ -0,0 +1,63 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Saving and loading settings and recordings as RON or JSON.
+pub trait Persist: Serialize + DeserializeOwned {
+    /// Whether to write with indentation and line breaks, for files people
+    /// are expected to read and edit.
+    const PRETTY: bool = true;
+
+    fn to_ron(&self) -> Result<String, ron::Error> {
+        if Self::PRETTY {
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+        } else {
+            ron::ser::to_string(self)
+        }
+    }
+
+    fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+
+    fn to_json(&self) -> serde_json::Result<String> {
+        if Self::PRETTY {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrbitCamBookmarks, OrbitCamConfig, OrbitCamLog};
+
+    #[test]
+    fn round_trips_in_both_formats() {
+        let config = OrbitCamConfig::default();
+        let ron = config.to_ron().unwrap();
+        let json = config.to_json().unwrap();
+        assert!(ron.contains('\n') && json.contains('\n'));
+        assert_eq!(
+            OrbitCamConfig::from_ron(&ron).unwrap().actions,
+            config.actions
+        );
+        assert_eq!(
+            OrbitCamConfig::from_json(&json).unwrap().actions,
+            config.actions
+        );
+
+        let log = OrbitCamLog::default();
+        let ron = log.to_ron().unwrap();
+        assert!(!ron.contains('\n'));
+        assert_eq!(OrbitCamLog::from_ron(&ron).unwrap(), log);
+
+        let bookmarks = OrbitCamBookmarks::default();
+        let json = bookmarks.to_json().unwrap();
+        assert_eq!(OrbitCamBookmarks::from_json(&json).unwrap(), bookmarks);
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use crate::{InputBinding, OrbitAction, OrbitCamConfig};
+
+const MODIFIERS: [KeyCode; 8] = [
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+];
+
+/// Captures the next input pressed and binds it to an action in an
+/// [`OrbitCamConfig`], for building rebinding menus.
+///
+/// Modifier keys held while pressing another input produce a chord, and a
+/// modifier pressed and released on its own is bound by itself. Pressing
+/// escape cancels the capture.
+///
+/// Input on the frame the capture starts is ignored, so the click on a
+/// rebind button isn't captured itself. Cameras ignore input while a capture
+/// is pending and on the frame it finishes, so the input being bound doesn't
+/// also move them.
+#[derive(Resource, Default, Debug)]
+pub struct OrbitCamRebind {
+    pending: Option<PendingRebind>,
+    finished: bool,
+}
+
+/// Which [`OrbitCamConfig`] a rebind changes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum RebindTarget {
+    /// The resource shared by cameras without their own config.
+    #[default]
+    Resource,
+    /// The config component on a camera. A camera without one is given a copy
+    /// of the resource to rebind, so the change only applies to it.
+    Camera(Entity),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PendingRebind {
+    action: OrbitAction,
+    target: RebindTarget,
+    replace: bool,
+    armed: bool,
+}
+
+impl OrbitCamRebind {
+    /// Replaces the bindings of `action` in `target` with the next input pressed.
+    pub fn capture(&mut self, action: OrbitAction, target: RebindTarget) {
+        self.pending = Some(PendingRebind {
+            action,
+            target,
+            replace: true,
+            armed: false,
+        });
+    }
+
+    /// Adds the next input pressed to the bindings of `action` in `target`.
+    pub fn capture_additional(&mut self, action: OrbitAction, target: RebindTarget) {
+        self.pending = Some(PendingRebind {
+            action,
+            target,
+            replace: false,
+            armed: false,
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// The action currently waiting for an input.
+    pub fn capturing(&self) -> Option<OrbitAction> {
+        self.pending.map(|pending| pending.action)
+    }
+
+    /// Whether cameras should ignore input this frame.
+    pub fn blocks_input(&self) -> bool {
+        self.pending.is_some() || self.finished
+    }
+}
+
+/// Sent when [`OrbitCamRebind`] binds an input to an action.
+#[derive(Event, Clone, Debug)]
+pub struct OrbitCamRebound {
+    pub action: OrbitAction,
+    pub target: RebindTarget,
+    pub binding: InputBinding,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn capture_rebind(
+    mut commands: Commands,
+    mut rebind: ResMut<OrbitCamRebind>,
+    mut config: ResMut<OrbitCamConfig>,
+    mut cameras: Query<&mut OrbitCamConfig>,
+    mut rebound: EventWriter<OrbitCamRebound>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+) {
+    rebind.finished = false;
+    let Some(pending) = &mut rebind.pending else {
+        return;
+    };
+    if !pending.armed {
+        pending.armed = true;
+        return;
+    }
+    let pending = *pending;
+
+    if keys.just_pressed(KeyCode::Escape) {
+        rebind.pending = None;
+        rebind.finished = true;
+        return;
+    }
+
+    let held = MODIFIERS
+        .into_iter()
+        .filter(|modifier| keys.pressed(*modifier))
+        .collect::<Vec<_>>();
+
+    let pressed = keys
+        .get_just_pressed()
+        .find(|key| !MODIFIERS.contains(key))
+        .map(|key| InputBinding::Key(*key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|b| InputBinding::Mouse(*b))
+        })
+        .or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+                .map(|button| InputBinding::Gamepad(*button))
+        });
+
+    let binding = match pressed {
+        Some(binding) if held.is_empty() => binding,
+        Some(binding) => InputBinding::chord(held, binding),
+        None => match keys.get_just_released().find(|key| MODIFIERS.contains(key)) {
+            Some(modifier) if held.is_empty() => InputBinding::Key(*modifier),
+            _ => return,
+        },
+    };
+
+    match pending.target {
+        RebindTarget::Resource => rebind_action(&mut config, pending, binding.clone()),
+        RebindTarget::Camera(entity) => match cameras.get_mut(entity) {
+            Ok(mut camera_config) => rebind_action(&mut camera_config, pending, binding.clone()),
+            Err(_) => {
+                let mut camera_config = config.clone();
+                rebind_action(&mut camera_config, pending, binding.clone());
+                commands.entity(entity).try_insert(camera_config);
+            }
+        },
+    }
+    rebind.pending = None;
+    rebind.finished = true;
+
+    rebound.send(OrbitCamRebound {
+        action: pending.action,
+        target: pending.target,
+        binding,
+    });
+}
+
+fn rebind_action(config: &mut OrbitCamConfig, pending: PendingRebind, binding: InputBinding) {
+    if pending.replace {
+        config.actions.unbind(pending.action);
+    }
+    config.actions.bind(pending.action, binding);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{process_bookmarks, testing::input_world, OrbitCam, OrbitCamBookmarks};
+
+    #[test]
+    fn ignores_the_click_that_starts_capture() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.init_resource::<Events<OrbitCamRebound>>();
+
+        world
+            .resource_mut::<OrbitCamRebind>()
+            .capture(OrbitAction::Cw, RebindTarget::Resource);
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(capture_rebind).unwrap();
+        assert_eq!(
+            world.resource::<OrbitCamRebind>().capturing(),
+            Some(OrbitAction::Cw)
+        );
+
+        let mut mouse = world.resource_mut::<ButtonInput<MouseButton>>();
+        mouse.clear();
+        mouse.press(MouseButton::Right);
+        world.run_system_once(capture_rebind).unwrap();
+
+        assert_eq!(world.resource::<OrbitCamRebind>().capturing(), None);
+        assert_eq!(
+            world
+                .resource::<OrbitCamConfig>()
+                .actions
+                .bindings(OrbitAction::Cw),
+            [InputBinding::Mouse(MouseButton::Right)]
+        );
+    }
+
+    #[test]
+    fn binds_without_applying_the_captured_input() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.init_resource::<Events<OrbitCamRebound>>();
+
+        let mut saved = OrbitCam::default();
+        saved.distance.hard_set(42.0);
+        let mut bookmarks = OrbitCamBookmarks::default();
+        bookmarks.store("1", &saved);
+        world.insert_resource(bookmarks);
+
+        let camera = world.spawn(OrbitCam::default()).id();
+        world
+            .resource_mut::<OrbitCamRebind>()
+            .capture(OrbitAction::RecallBookmark(1), RebindTarget::Camera(camera));
+        let frame = |world: &mut World| {
+            world.run_system_once(capture_rebind).unwrap();
+            world.run_system_once(process_bookmarks).unwrap();
+        };
+
+        frame(&mut world);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyR);
+        frame(&mut world);
+
+        assert_eq!(world.resource::<OrbitCamRebind>().capturing(), None);
+        assert_eq!(world.get::<OrbitCam>(camera).unwrap().distance.target, 4.0);
+        assert_eq!(
+            world
+                .get::<OrbitCamConfig>(camera)
+                .unwrap()
+                .actions
+                .bindings(OrbitAction::RecallBookmark(1)),
+            [InputBinding::Key(KeyCode::KeyR)]
+        );
+        assert!(world
+            .resource::<OrbitCamConfig>()
+            .actions
+            .bindings(OrbitAction::RecallBookmark(1))
+            .contains(&InputBinding::Key(KeyCode::Digit1)));
+
+        let mut keys = world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.clear();
+        keys.release(KeyCode::KeyR);
+        frame(&mut world);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyR);
+        frame(&mut world);
+        assert_eq!(world.get::<OrbitCam>(camera).unwrap().distance.target, 42.0);
+    }
+}
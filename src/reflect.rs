@@ -0,0 +1,60 @@
+use bevy::{
+    math::{Quat, Vec3},
+    reflect::{reflect_remote, FromReflect, PartialReflect, ReflectRef},
+};
+use buttery::{Rotate, TransformComponent, Translate};
+
+/// Reflects a buttery component as its public fields, so inspectors can show
+/// and edit the smoothed parts of an [`OrbitCam`](crate::OrbitCam).
+///
+/// `FromReflect` is written by hand, as the component has a private field and
+/// has to be built through its constructor.
+macro_rules! reflect_smoothed {
+    ($wrapper:ident, $smoothed:ty, $attribute:ty) => {
+        #[reflect_remote(TransformComponent<$smoothed>)]
+        #[reflect(from_reflect = false)]
+        pub struct $wrapper {
+            pub retention: f32,
+            pub current: $attribute,
+            pub target: $attribute,
+        }
+
+        impl FromReflect for $wrapper {
+            fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
+                let ReflectRef::Struct(fields) = reflect.reflect_ref() else {
+                    return None;
+                };
+                let field = |name| fields.field(name).and_then(<$attribute>::from_reflect);
+
+                let retention = fields.field("retention").and_then(f32::from_reflect)?;
+                let mut component = TransformComponent::new(retention, field("current")?);
+                component.target = field("target")?;
+                Some($wrapper(component))
+            }
+        }
+    };
+}
+
+reflect_smoothed!(SmoothedQuat, Rotate, Quat);
+reflect_smoothed!(SmoothedF32, Translate<f32>, f32);
+reflect_smoothed!(SmoothedVec3, Translate<Vec3>, Vec3);
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::GetPath;
+
+    use super::*;
+    use crate::{OrbitCam, OrbitCamSnapshot};
+
+    #[test]
+    fn reflects_smoothed_fields() {
+        let mut orbcam = OrbitCam::default();
+        *orbcam.path_mut::<f32>("distance.target").unwrap() = 12.0;
+        *orbcam.path_mut::<Vec3>("focus_position.current").unwrap() = Vec3::X;
+        assert_eq!(orbcam.distance.target, 12.0);
+        assert_eq!(orbcam.focus_position.current, Vec3::X);
+
+        let copy = OrbitCam::from_reflect(orbcam.clone_value().as_ref()).unwrap();
+        assert_eq!(OrbitCamSnapshot::from(copy), OrbitCamSnapshot::from(orbcam));
+    }
+}
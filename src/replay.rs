@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "persist")]
+use crate::Persist;
+use crate::{
+    relative_to_parent, OrbitCam, OrbitCamSnapshot, OrbitCamState, OrbitCamTerrain,
+    OrbitInputDelta, Transition,
+};
+
+/// A recording of the input applied to a camera, frame by frame.
+///
+/// Logs of cameras with a [`TiltCurve::Custom`](crate::TiltCurve::Custom) can
+/// be replayed but not saved, as the curve can't be serialized.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrbitCamLog {
+    /// The camera when recording started, which replays start from.
+    #[serde(default)]
+    pub initial: Option<OrbitCamSnapshot>,
+    pub frames: Vec<OrbitCamFrame>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrbitCamFrame {
+    /// The time the camera was driven forward by this frame.
+    pub delta_secs: f32,
+    /// The input applied this frame, in the order it was applied.
+    pub inputs: Vec<OrbitCamInput>,
+    /// The transform of the camera's body this frame, which is read live if missing.
+    #[serde(default)]
+    pub body: Option<Transform>,
+    /// The focus position this frame, relative to the body, which is tracked
+    /// live if missing.
+    #[serde(default)]
+    pub focus: Option<Vec3>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OrbitCamInput {
+    Delta(OrbitInputDelta),
+    /// A jump to a bookmarked view, which replays without the bookmarks.
+    Recall {
+        state: OrbitCamState,
+        transition: Transition,
+    },
+    /// The whole camera, after something other than input changed it, such
+    /// as [`fly_to`](OrbitCam::fly_to) or [`snap_to`](OrbitCam::snap_to).
+    Set(Box<OrbitCamSnapshot>),
+}
+
+/// Logs grow by a frame at a time, so they're written compactly.
+#[cfg(feature = "persist")]
+impl Persist for OrbitCamLog {
+    const PRETTY: bool = false;
+}
+
+/// Records the input applied to the camera it's attached to, along with
+/// anything else that changes it.
+#[derive(Component, Clone, Debug, Default)]
+pub struct OrbitCamRecorder {
+    pub log: OrbitCamLog,
+    /// The camera as replaying the log so far would leave it, to catch
+    /// changes that weren't recorded as input.
+    replica: Option<OrbitCam>,
+}
+
+impl OrbitCamRecorder {
+    pub fn record(&mut self, delta: OrbitInputDelta) {
+        self.push(OrbitCamInput::Delta(delta));
+        if let Some(replica) = &mut self.replica {
+            replica.apply_input(delta);
+        }
+    }
+
+    pub fn record_recall(&mut self, state: OrbitCamState, transition: Transition) {
+        self.push(OrbitCamInput::Recall { state, transition });
+        if let Some(replica) = &mut self.replica {
+            replica.transition_to(state, transition);
+        }
+    }
+
+    /// Records `orbcam` whole if it was changed by anything but recorded input.
+    pub(crate) fn record_changes(&mut self, orbcam: &OrbitCam) {
+        let Some(replica) = &self.replica else {
+            return;
+        };
+
+        let snapshot = OrbitCamSnapshot::from(orbcam);
+        if snapshot != OrbitCamSnapshot::from(replica) {
+            self.push(OrbitCamInput::Set(Box::new(snapshot)));
+        }
+    }
+
+    /// Records where the body and focus were as `orbcam` was driven.
+    pub(crate) fn record_drive(&mut self, orbcam: &OrbitCam, body: Transform) {
+        if let Some(frame) = self.log.frames.last_mut() {
+            frame.body = Some(body);
+            frame.focus = Some(orbcam.focus_position.target);
+        }
+        self.replica = Some(orbcam.clone());
+    }
+
+    fn push(&mut self, input: OrbitCamInput) {
+        if let Some(frame) = self.log.frames.last_mut() {
+            frame.inputs.push(input);
+        }
+    }
+}
+
+/// Replays a log into the camera it's attached to instead of live input.
+///
+/// The camera is reset to the state the log was recorded from, and its body
+/// and focus follow the positions they were recorded at, so this reproduces
+/// the recorded transforms exactly as long as the terrain is the same. The
+/// component removes itself once the log runs out, handing the camera back to
+/// live input.
+#[derive(Component, Clone, Debug)]
+pub struct OrbitCamReplay {
+    pub log: OrbitCamLog,
+    pub frame: usize,
+}
+
+impl OrbitCamReplay {
+    pub fn new(log: OrbitCamLog) -> Self {
+        OrbitCamReplay { log, frame: 0 }
+    }
+}
+
+pub fn begin_recording_frame(
+    mut recorders: Query<(&mut OrbitCamRecorder, &OrbitCam)>,
+    time: Res<Time>,
+) {
+    for (mut recorder, orbcam) in recorders.iter_mut() {
+        if recorder.log.frames.is_empty() {
+            recorder.log.initial = Some(orbcam.into());
+            recorder.replica = Some(orbcam.clone());
+        }
+        recorder.log.frames.push(OrbitCamFrame {
+            delta_secs: time.delta_secs(),
+            ..default()
+        });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn replay_orbitcams(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut OrbitCam,
+        &mut OrbitCamReplay,
+        Option<&OrbitCamTerrain>,
+        Option<&Parent>,
+    )>,
+    transforms: Query<&GlobalTransform>,
+    terrain: Option<Res<OrbitCamTerrain>>,
+) {
+    for (entity, mut transform, mut orbcam, mut replay, camera_terrain, parent) in query.iter_mut()
+    {
+        let Some(frame) = replay.log.frames.get(replay.frame) else {
+            commands.entity(entity).remove::<OrbitCamReplay>();
+            continue;
+        };
+
+        if replay.frame == 0 {
+            if let Some(initial) = replay.log.initial {
+                *orbcam = initial.into();
+            }
+        }
+
+        // Same order as live input, which is applied before `update_orbitcams`.
+        for input in &frame.inputs {
+            match input {
+                OrbitCamInput::Delta(delta) => orbcam.apply_input(*delta),
+                OrbitCamInput::Recall { state, transition } => {
+                    orbcam.transition_to(*state, *transition)
+                }
+                OrbitCamInput::Set(snapshot) => *orbcam = (**snapshot).into(),
+            }
+        }
+        let body = frame
+            .body
+            .unwrap_or_else(|| orbcam.body_transform(&transforms));
+        match frame.focus {
+            Some(focus) => orbcam.focus_position.target = focus,
+            None => orbcam.track_focus(&transforms, &body),
+        }
+        let terrain = camera_terrain.or(terrain.as_deref());
+        let new_transform = body * orbcam.drive_over(frame.delta_secs, terrain);
+        *transform = relative_to_parent(new_transform, parent, &transforms);
+
+        replay.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{OrbitFlightFinished, OrbitFocus, OrbitSurface, TiltCurve};
+
+    #[test]
+    fn unchanged_cameras_record_nothing() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<OrbitFlightFinished>>();
+        let camera = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam {
+                    tilt_curve: TiltCurve::Custom(|_| (0.0, 1.0)),
+                    ..default()
+                },
+                OrbitCamRecorder::default(),
+            ))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((begin_recording_frame, crate::update_orbitcams).chain());
+        for _ in 0..5 {
+            schedule.run(&mut world);
+        }
+
+        let log = &world.get::<OrbitCamRecorder>(camera).unwrap().log;
+        assert_eq!(log.frames.len(), 5);
+        assert!(log.frames.iter().all(|frame| frame.inputs.is_empty()));
+    }
+
+    #[test]
+    fn replays_from_the_recorded_start() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let target = world.spawn(GlobalTransform::from_xyz(5.0, 0.0, 0.0)).id();
+
+        let mut live = OrbitCam {
+            surface: OrbitSurface::Plane,
+            focus: OrbitFocus::Entity(target),
+            ..default()
+        };
+        live.distance.hard_set(7.0);
+        let mut recorder = OrbitCamRecorder::default();
+        recorder.log.initial = Some((&live).into());
+
+        let delta = OrbitInputDelta {
+            yaw: 0.3,
+            shift: Vec3::X,
+            ..default()
+        };
+        let mut expected = Transform::IDENTITY;
+        for _ in 0..3 {
+            recorder.log.frames.push(OrbitCamFrame {
+                delta_secs: 0.02,
+                inputs: vec![OrbitCamInput::Delta(delta)],
+                ..default()
+            });
+            live.apply_input(delta);
+            expected = live.drive(0.02);
+        }
+
+        let camera = world
+            .spawn((
+                Transform::IDENTITY,
+                OrbitCam::default(),
+                OrbitCamReplay::new(recorder.log),
+            ))
+            .id();
+        for _ in 0..3 {
+            world.run_system_once(replay_orbitcams).unwrap();
+        }
+
+        assert_eq!(*world.get::<Transform>(camera).unwrap(), expected);
+    }
+}
@@ -0,0 +1,307 @@
+use bevy::{
+    ecs::{query::QueryData, system::SystemParam},
+    prelude::*,
+    render::camera::NormalizedRenderTarget,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    OrbitCam, OrbitCamConfig, OrbitCamInertia, OrbitCamRebind, OrbitCamRecorder, OrbitCamState,
+    OrbitInputDelta, Transition,
+};
+
+/// Chooses which [`OrbitCam`]s receive input.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum OrbitCamInputRouting {
+    /// Every camera receives the same input.
+    #[default]
+    All,
+    /// Only the camera whose viewport was last clicked or touched, while its
+    /// window is focused. See [`OrbitCamFocusedViewport`].
+    FocusedViewport,
+    /// Only cameras whose viewport contains the cursor or touch.
+    UnderCursor,
+    /// Only cameras marked with [`OrbitCamInputTarget`].
+    Marked,
+}
+
+/// Marks a camera as receiving input under [`OrbitCamInputRouting::Marked`].
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct OrbitCamInputTarget;
+
+/// The camera receiving input under [`OrbitCamInputRouting::FocusedViewport`].
+///
+/// Updated by [`focus_viewport`], and can be set directly to move focus
+/// without a click.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct OrbitCamFocusedViewport(pub Option<Entity>);
+
+/// Focuses the topmost orbit camera whose viewport was just clicked or touched.
+pub fn focus_viewport(
+    mut focused: ResMut<OrbitCamFocusedViewport>,
+    cameras: Query<(Entity, &Camera), With<OrbitCam>>,
+    windows: Query<&Window>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+) {
+    let clicked = mouse.get_just_pressed().next().is_some();
+    let touched = touches
+        .iter_just_pressed()
+        .map(|touch| touch.position())
+        .collect::<Vec<_>>();
+    if !clicked && touched.is_empty() {
+        return;
+    }
+
+    let hit = cameras
+        .iter()
+        .filter(|(_, camera)| {
+            let Some(rect) = camera.logical_viewport_rect() else {
+                return false;
+            };
+            let window = camera_window(camera, &windows, &primary);
+            let cursor = window.filter(|_| clicked).and_then(Window::cursor_position);
+
+            cursor
+                .into_iter()
+                .chain(touched.iter().copied())
+                .any(|pointer| rect.contains(pointer))
+        })
+        .max_by_key(|(_, camera)| camera.order);
+
+    if let Some((entity, _)) = hit {
+        focused.0 = Some(entity);
+    }
+}
+
+fn camera_window<'a>(
+    camera: &Camera,
+    windows: &'a Query<&Window>,
+    primary: &Query<Entity, With<PrimaryWindow>>,
+) -> Option<&'a Window> {
+    match camera.target.normalize(primary.get_single().ok())? {
+        NormalizedRenderTarget::Window(window) => windows.get(window.entity()).ok(),
+        _ => None,
+    }
+}
+
+/// An orbit camera along with everything that decides where its input comes from.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct RoutedOrbitCam {
+    pub entity: Entity,
+    pub orbit: &'static mut OrbitCam,
+    pub view: Option<&'static Camera>,
+    pub config: Option<&'static OrbitCamConfig>,
+    pub marked: Has<OrbitCamInputTarget>,
+    pub recorder: Option<&'static mut OrbitCamRecorder>,
+    pub inertia: Option<&'static mut OrbitCamInertia>,
+}
+
+impl RoutedOrbitCamItem<'_> {
+    /// Applies `delta` to the camera, recording it if the camera has a recorder
+    /// and sampling it if the camera has inertia.
+    pub fn apply_input(&mut self, delta: OrbitInputDelta) {
+        if self.apply_impulse(delta) {
+            if let Some(inertia) = &mut self.inertia {
+                inertia.sample(delta);
+            }
+        }
+    }
+
+    /// Applies a one-off `delta`, like a scroll step, recording it if the
+    /// camera has a recorder. Unlike [`apply_input`](Self::apply_input) it's
+    /// not sampled, so it doesn't carry on once input stops.
+    ///
+    /// Returns whether there was anything to apply.
+    pub fn apply_impulse(&mut self, delta: OrbitInputDelta) -> bool {
+        if delta == OrbitInputDelta::default() {
+            return false;
+        }
+
+        self.orbit.apply_input(delta);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(delta);
+        }
+        true
+    }
+
+    /// Sends the camera to `state`, recording the jump if the camera has a
+    /// recorder and stopping any coasting.
+    pub fn recall(&mut self, state: OrbitCamState, transition: Transition) {
+        self.orbit.transition_to(state, transition);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_recall(state, transition);
+        }
+        if let Some(inertia) = &mut self.inertia {
+            inertia.stop();
+        }
+    }
+
+    /// Jumps the camera straight to `state`, like [`OrbitCam::snap_to`], but
+    /// also recording the jump and stopping any coasting.
+    pub fn snap_to(&mut self, state: OrbitCamState) {
+        self.recall(state, Transition::Snap);
+    }
+
+    /// Tells the camera's inertia, if any, that a drag or touch is still held.
+    pub fn hold_input(&mut self) {
+        if let Some(inertia) = &mut self.inertia {
+            inertia.hold();
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct InputRouter<'w, 's> {
+    routing: Res<'w, OrbitCamInputRouting>,
+    focused: Res<'w, OrbitCamFocusedViewport>,
+    rebind: Res<'w, OrbitCamRebind>,
+    windows: Query<'w, 's, &'static Window>,
+    primary: Query<'w, 's, Entity, With<PrimaryWindow>>,
+}
+
+impl InputRouter<'_, '_> {
+    /// The window `camera` renders to, if any.
+    pub fn window(&self, camera: &Camera) -> Option<&Window> {
+        camera_window(camera, &self.windows, &self.primary)
+    }
+
+    /// The cursor position in the window `camera` renders to.
+    pub fn cursor(&self, camera: &Camera) -> Option<Vec2> {
+        self.window(camera)?.cursor_position()
+    }
+
+    /// Whether a camera should receive input, where `pointer` is the window
+    /// position of the cursor or touch driving it.
+    ///
+    /// No camera does while [`OrbitCamRebind`] is capturing input.
+    pub fn accepts(
+        &self,
+        entity: Entity,
+        camera: Option<&Camera>,
+        marked: bool,
+        pointer: Option<Vec2>,
+    ) -> bool {
+        if self.rebind.blocks_input() {
+            return false;
+        }
+
+        match *self.routing {
+            OrbitCamInputRouting::All => true,
+            OrbitCamInputRouting::Marked => marked,
+            OrbitCamInputRouting::FocusedViewport => {
+                self.focused.0 == Some(entity)
+                    && camera
+                        .and_then(|camera| self.window(camera))
+                        .is_some_and(|window| window.focused)
+            }
+            OrbitCamInputRouting::UnderCursor => camera
+                .and_then(Camera::logical_viewport_rect)
+                .zip(pointer)
+                .is_some_and(|(rect, pointer)| rect.contains(pointer)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, render::camera::Viewport};
+
+    use super::*;
+    use crate::testing::{input_world, spawn_view, spawn_window};
+
+    /// A camera covering the left or right half of the window.
+    fn half(right: bool) -> Camera {
+        Camera {
+            viewport: Some(Viewport {
+                physical_position: UVec2::new(if right { 400 } else { 0 }, 0),
+                physical_size: UVec2::new(400, 600),
+                ..default()
+            }),
+            ..default()
+        }
+    }
+
+    /// Runs a frame of holding a key, returning which of `cameras` turned.
+    fn turned(world: &mut World, cameras: &[Entity]) -> Vec<bool> {
+        for &camera in cameras {
+            world.entity_mut(camera).insert(OrbitCam::default());
+        }
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ArrowLeft);
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(0.1));
+        world.run_system_once(OrbitCam::process_input).unwrap();
+
+        cameras
+            .iter()
+            .map(|&camera| world.get::<OrbitCam>(camera).unwrap().up.target != Quat::IDENTITY)
+            .collect()
+    }
+
+    #[test]
+    fn marked_routing_only_drives_marked_cameras() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.insert_resource(OrbitCamInputRouting::Marked);
+        let marked = world.spawn((OrbitCam::default(), OrbitCamInputTarget)).id();
+        let unmarked = world.spawn(OrbitCam::default()).id();
+
+        assert_eq!(turned(&mut world, &[marked, unmarked]), [true, false]);
+    }
+
+    #[test]
+    fn under_cursor_routing_follows_the_cursor() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.insert_resource(OrbitCamInputRouting::UnderCursor);
+        let window = spawn_window(&mut world, Vec2::new(600.0, 300.0));
+        let left = spawn_view(&mut world, half(false), OrbitCam::default());
+        let right = spawn_view(&mut world, half(true), OrbitCam::default());
+
+        assert_eq!(turned(&mut world, &[left, right]), [false, true]);
+
+        world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(Vec2::new(200.0, 300.0)));
+        assert_eq!(turned(&mut world, &[left, right]), [true, false]);
+    }
+
+    #[test]
+    fn clicking_a_viewport_focuses_it() {
+        let mut world = input_world(OrbitCamConfig::default());
+        world.insert_resource(OrbitCamInputRouting::FocusedViewport);
+        world.init_resource::<Touches>();
+        let window = spawn_window(&mut world, Vec2::new(600.0, 300.0));
+        let left = spawn_view(&mut world, half(false), OrbitCam::default());
+        let right = spawn_view(&mut world, half(true), OrbitCam::default());
+
+        // Nothing is focused until a viewport is clicked.
+        assert_eq!(turned(&mut world, &[left, right]), [false, false]);
+
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(focus_viewport).unwrap();
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .release(MouseButton::Left);
+        assert_eq!(world.resource::<OrbitCamFocusedViewport>().0, Some(right));
+        assert_eq!(turned(&mut world, &[left, right]), [false, true]);
+
+        // Moving the cursor off without clicking keeps the focus.
+        world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(Vec2::new(200.0, 300.0)));
+        assert_eq!(turned(&mut world, &[left, right]), [false, true]);
+
+        world.get_mut::<Window>(window).unwrap().focused = false;
+        assert_eq!(turned(&mut world, &[left, right]), [false, false]);
+    }
+}
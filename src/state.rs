@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use buttery::{Smoothed, TransformComponent};
+use serde::{Deserialize, Serialize};
+
+use crate::{OrbitBody, OrbitCam, OrbitCamSlot, OrbitFlight, OrbitFocus, OrbitSurface, TiltCurve};
+
+/// The values of every smoothed component of an [`OrbitCam`].
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OrbitCamState {
+    pub up: Quat,
+    pub inclination: f32,
+    pub distance: f32,
+    pub target_height: f32,
+    pub min: f32,
+}
+
+impl OrbitCam {
+    pub fn state(&self, slot: OrbitCamSlot) -> OrbitCamState {
+        OrbitCamState {
+            up: slot.get(&self.up),
+            inclination: slot.get(&self.inclination),
+            distance: slot.get(&self.distance),
+            target_height: slot.get(&self.target_height),
+            min: slot.get(&self.min),
+        }
+    }
+
+    pub fn set_state(&mut self, state: OrbitCamState, slot: OrbitCamSlot) {
+        slot.set(&mut self.up, state.up);
+        slot.set(&mut self.inclination, state.inclination);
+        slot.set(&mut self.distance, state.distance);
+        slot.set(&mut self.target_height, state.target_height);
+        slot.set(&mut self.min, state.min);
+    }
+
+    /// Jumps straight to `state` without easing, cancelling any flight.
+    ///
+    /// The inclination is limited by `tilt_curve` first, so it doesn't ease
+    /// back within the curve afterwards. This doesn't stop an
+    /// [`OrbitCamInertia`](crate::OrbitCamInertia), which
+    /// [`RoutedOrbitCamItem::snap_to`](crate::RoutedOrbitCamItem::snap_to) does.
+    pub fn snap_to(&mut self, state: OrbitCamState) {
+        let state = OrbitCamState {
+            inclination: self.tilt_curve.clamp(state.inclination, state.distance),
+            ..state
+        };
+        self.cancel_flight();
+        self.set_state(state, OrbitCamSlot::Target);
+        self.set_state(state, OrbitCamSlot::Current);
+    }
+}
+
+/// How closely each smoothed component of an [`OrbitCam`] follows its target.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OrbitCamRetention {
+    pub up: f32,
+    pub inclination: f32,
+    pub distance: f32,
+    pub target_height: f32,
+    pub min: f32,
+    pub focus: f32,
+}
+
+/// Everything needed to restore an [`OrbitCam`] exactly, mid-ease or mid-flight.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OrbitCamSnapshot {
+    pub current: OrbitCamState,
+    pub target: OrbitCamState,
+    pub retention: OrbitCamRetention,
+    pub flight: Option<OrbitFlight>,
+    pub focus: OrbitFocus,
+    pub focus_current: Vec3,
+    pub focus_target: Vec3,
+    pub body: Option<OrbitBody>,
+    pub surface: OrbitSurface,
+    pub tilt_curve: TiltCurve,
+}
+
+impl From<&OrbitCam> for OrbitCamSnapshot {
+    fn from(orbcam: &OrbitCam) -> Self {
+        OrbitCamSnapshot {
+            current: orbcam.state(OrbitCamSlot::Current),
+            target: orbcam.state(OrbitCamSlot::Target),
+            retention: OrbitCamRetention {
+                up: orbcam.up.retention,
+                inclination: orbcam.inclination.retention,
+                distance: orbcam.distance.retention,
+                target_height: orbcam.target_height.retention,
+                min: orbcam.min.retention,
+                focus: orbcam.focus_position.retention,
+            },
+            flight: orbcam.flight,
+            focus: orbcam.focus,
+            focus_current: orbcam.focus_position.current,
+            focus_target: orbcam.focus_position.target,
+            body: orbcam.body,
+            surface: orbcam.surface,
+            tilt_curve: orbcam.tilt_curve,
+        }
+    }
+}
+
+impl From<OrbitCam> for OrbitCamSnapshot {
+    fn from(orbcam: OrbitCam) -> Self {
+        OrbitCamSnapshot::from(&orbcam)
+    }
+}
+
+impl From<OrbitCamSnapshot> for OrbitCam {
+    fn from(snapshot: OrbitCamSnapshot) -> Self {
+        let OrbitCamSnapshot {
+            current,
+            target,
+            retention,
+            flight,
+            focus,
+            focus_current,
+            focus_target,
+            body,
+            surface,
+            tilt_curve,
+        } = snapshot;
+
+        OrbitCam {
+            up: component(retention.up, current.up, target.up),
+            inclination: component(
+                retention.inclination,
+                current.inclination,
+                target.inclination,
+            ),
+            distance: component(retention.distance, current.distance, target.distance),
+            target_height: component(
+                retention.target_height,
+                current.target_height,
+                target.target_height,
+            ),
+            min: component(retention.min, current.min, target.min),
+            flight,
+            focus,
+            focus_position: component(retention.focus, focus_current, focus_target),
+            body,
+            surface,
+            tilt_curve,
+        }
+    }
+}
+
+fn component<T: Smoothed>(
+    retention: f32,
+    current: T::Attribute,
+    target: T::Attribute,
+) -> TransformComponent<T> {
+    let mut component = TransformComponent::new(retention, current);
+    component.target = target;
+    component
+}
+
+impl Clone for OrbitCam {
+    fn clone(&self) -> Self {
+        OrbitCamSnapshot::from(self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{apply_inertia, OrbitCamInertia, OrbitInputDelta, RoutedOrbitCam};
+
+    #[test]
+    fn snaps_within_the_tilt_curve() {
+        let mut orbcam = OrbitCam {
+            tilt_curve: TiltCurve::Fixed { min: 0.0, max: 0.5 },
+            ..default()
+        };
+        let state = OrbitCamState {
+            inclination: 1.2,
+            ..orbcam.state(OrbitCamSlot::Target)
+        };
+
+        orbcam.snap_to(state);
+        orbcam.drive(0.1);
+        assert_eq!(orbcam.inclination.current, 0.5);
+        assert_eq!(orbcam.inclination.target, 0.5);
+    }
+
+    #[test]
+    fn snapping_stops_coasting() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let camera = world
+            .spawn((OrbitCam::default(), OrbitCamInertia::default()))
+            .id();
+
+        world
+            .get_mut::<OrbitCamInertia>(camera)
+            .unwrap()
+            .sample(OrbitInputDelta {
+                pan: Vec2::new(0.05, 0.0),
+                ..default()
+            });
+        for _ in 0..2 {
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            world.run_system_once(apply_inertia).unwrap();
+        }
+        assert!(world.get::<OrbitCamInertia>(camera).unwrap().coasting());
+
+        let state = OrbitCamState {
+            up: Quat::from_rotation_x(0.7),
+            ..OrbitCam::default().state(OrbitCamSlot::Target)
+        };
+        world
+            .run_system_once(move |mut cameras: Query<RoutedOrbitCam>| {
+                cameras.single_mut().snap_to(state);
+            })
+            .unwrap();
+        world.run_system_once(apply_inertia).unwrap();
+
+        let orbcam = world.get::<OrbitCam>(camera).unwrap();
+        assert_eq!(orbcam.state(OrbitCamSlot::Target), state);
+        assert_eq!(orbcam.state(OrbitCamSlot::Current), state);
+    }
+}
@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The shape an [`OrbitCam`](crate::OrbitCam) moves over.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Reflect, Serialize, Deserialize)]
+pub enum OrbitSurface {
+    /// A sphere around the focus, which panning rotates `up` across.
+    #[default]
+    Sphere,
+    /// A horizontal plane through the focus, which panning moves the focus across.
+    /// `up` only yaws, and `min` is the height above the plane.
+    Plane,
+    /// An ellipsoid around the focus with its poles on the Y axis, such as an
+    /// oblate planet. `up` points along the surface normal, and `target_height`
+    /// and `min` are altitudes above the surface.
+    Ellipsoid { equatorial: f32, polar: f32 },
+}
+
+impl OrbitSurface {
+    /// Removes any part of `up` the surface doesn't allow.
+    pub fn constrain(&self, up: Quat) -> Quat {
+        match self {
+            OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => up,
+            OrbitSurface::Plane => {
+                let forward = up * Vec3::Z;
+                if forward.xz() == Vec2::ZERO {
+                    return Quat::IDENTITY;
+                }
+                Quat::from_rotation_y(forward.x.atan2(forward.z))
+            }
+        }
+    }
+
+    /// How far the surface raised to `height` is from the focus, for scaling
+    /// movement across it.
+    pub fn radius(&self, height: f32) -> f32 {
+        match self {
+            OrbitSurface::Sphere | OrbitSurface::Plane => height,
+            OrbitSurface::Ellipsoid { equatorial, .. } => equatorial + height,
+        }
+    }
+
+    /// The camera position relative to the focus, `arm` away from the orbit
+    /// point at `height` above the surface under `up`, and pushed out to at
+    /// least `min` from the surface.
+    pub(crate) fn place(&self, up: Quat, height: f32, arm: Vec3, min: f32) -> Vec3 {
+        match *self {
+            OrbitSurface::Sphere => {
+                let mut position = Vec3::Y * height + arm;
+                // Raises the camera straight up until it's `min` from the focus.
+                if position.length() < min {
+                    position.y = (min * min - position.xz().length_squared()).sqrt();
+                }
+                up * position
+            }
+            OrbitSurface::Plane => {
+                let mut position = Vec3::Y * height + arm;
+                position.y = position.y.max(min);
+                up * position
+            }
+            OrbitSurface::Ellipsoid { equatorial, polar } => {
+                let normal = up * Vec3::Y;
+                let radii = Vec3::new(equatorial, polar, equatorial);
+
+                // The point on the surface whose normal is `normal`.
+                let scaled = radii * radii * normal;
+                let surface = scaled / scaled.dot(normal).sqrt();
+
+                let mut position = surface + normal * height + up * arm;
+                // Altitude is measured down the normal, so it matches `height`.
+                let altitude = ray_ellipsoid_distance(position, -normal, radii);
+                if let Some(altitude) = altitude.filter(|altitude| *altitude < min) {
+                    position += normal * (min - altitude);
+                }
+                position
+            }
+        }
+    }
+
+    /// Where to sample [`SurfaceHeight`](crate::SurfaceHeight) under `position`,
+    /// relative to `focus`.
+    pub(crate) fn terrain_point(&self, position: Vec3, focus: Vec3) -> Vec3 {
+        match self {
+            OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => position.normalize_or(Vec3::Y),
+            OrbitSurface::Plane => (focus + position).with_y(0.0),
+        }
+    }
+
+    /// The direction `up` points when over `point`, a point on the surface
+    /// raised to `height` relative to the focus.
+    pub(crate) fn normal_at(&self, point: Vec3, height: f32) -> Vec3 {
+        match *self {
+            OrbitSurface::Sphere => point.normalize_or(Vec3::Y),
+            OrbitSurface::Plane => Vec3::Y,
+            OrbitSurface::Ellipsoid { equatorial, polar } => {
+                let radii = Vec3::new(equatorial, polar, equatorial) + height;
+                (point / (radii * radii)).normalize_or(Vec3::Y)
+            }
+        }
+    }
+
+    /// What to remember of a grab starting at `hit`, relative to `focus`, to
+    /// hold it under the cursor with [`grab`](Self::grab).
+    pub(crate) fn grab_anchor(&self, hit: Vec3, focus: Vec3) -> Vec3 {
+        match self {
+            OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => hit,
+            // The focus moves while grabbing a plane, so the grab is
+            // remembered in the body's frame instead.
+            OrbitSurface::Plane => hit + focus,
+        }
+    }
+
+    /// The rotation of `up` and the shift of the focus which bring the grabbed
+    /// `anchor` back under the cursor, now over `hit` relative to `focus`.
+    ///
+    /// This is exact on spheres and planes. Turning `up` doesn't turn the view
+    /// rigidly over an ellipsoid, so there each call only closes most of the
+    /// gap, and holding the grab over a few frames settles it.
+    pub(crate) fn grab(&self, anchor: Vec3, hit: Vec3, focus: Vec3, height: f32) -> (Quat, Vec3) {
+        match self {
+            // `up` follows the surface normal, which on an ellipsoid isn't the
+            // direction to the point.
+            OrbitSurface::Sphere | OrbitSurface::Ellipsoid { .. } => {
+                let normal = |point| self.normal_at(point, height);
+                (rotation_arc(normal(hit), normal(anchor)), Vec3::ZERO)
+            }
+            OrbitSurface::Plane => (Quat::IDENTITY, anchor - (hit + focus)),
+        }
+    }
+
+    /// Where `ray`, relative to the focus, hits the surface raised to `height`.
+    pub(crate) fn hit(&self, ray: Ray3d, height: f32) -> Option<Vec3> {
+        match *self {
+            OrbitSurface::Sphere => ray_sphere(ray, height),
+            OrbitSurface::Plane => {
+                let distance =
+                    ray.intersect_plane(Vec3::Y * height, InfinitePlane3d::new(Vec3::Y))?;
+                Some(ray.get_point(distance))
+            }
+            OrbitSurface::Ellipsoid { equatorial, polar } => {
+                let radii = Vec3::new(equatorial, polar, equatorial) + height;
+                ray_ellipsoid(ray, radii)
+            }
+        }
+    }
+}
+
+/// The rotation from `from` to `to`, which unlike [`Quat::from_rotation_arc`]
+/// doesn't round tiny angles down to nothing, so slow grabs don't stick.
+fn rotation_arc(from: Vec3, to: Vec3) -> Quat {
+    let cross = from.cross(to);
+    match cross.try_normalize() {
+        Some(axis) => Quat::from_axis_angle(axis, cross.length().atan2(from.dot(to))),
+        None => Quat::from_rotation_arc(from, to),
+    }
+}
+
+fn ray_ellipsoid(ray: Ray3d, radii: Vec3) -> Option<Vec3> {
+    let t = ray_ellipsoid_distance(ray.origin, *ray.direction, radii)?;
+    (t >= 0.0).then(|| ray.get_point(t))
+}
+
+/// How far along `direction` the line through `origin` first crosses the
+/// ellipsoid, which is negative if `origin` is inside it.
+fn ray_ellipsoid_distance(origin: Vec3, direction: Vec3, radii: Vec3) -> Option<f32> {
+    // Squashes the ellipsoid into a unit sphere, where distances along the
+    // ray stay proportional.
+    let origin = origin / radii;
+    let direction = direction / radii;
+
+    let a = direction.length_squared();
+    let b = origin.dot(direction);
+    let c = origin.length_squared() - 1.0;
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    Some((-b - discriminant.sqrt()) / a)
+}
+
+fn ray_sphere(ray: Ray3d, radius: f32) -> Option<Vec3> {
+    let b = ray.origin.dot(*ray.direction);
+    let c = ray.origin.length_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = -b - discriminant.sqrt();
+    (t >= 0.0).then(|| ray.get_point(t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrbitCam, OrbitInputDelta};
+
+    const EARTHLIKE: OrbitSurface = OrbitSurface::Ellipsoid {
+        equatorial: 10.0,
+        polar: 7.0,
+    };
+
+    fn tilted() -> Quat {
+        Quat::from_rotation_z(0.7) * Quat::from_rotation_x(0.3)
+    }
+
+    #[test]
+    fn planes_only_yaw() {
+        let up = OrbitSurface::Plane.constrain(tilted() * Quat::from_rotation_y(0.4));
+        assert!((up * Vec3::Y).abs_diff_eq(Vec3::Y, 1e-6));
+        assert!(up.angle_between(Quat::IDENTITY) > 0.1);
+    }
+
+    #[test]
+    fn planes_ignore_surface_rotation() {
+        let mut orbcam = OrbitCam {
+            surface: OrbitSurface::Plane,
+            ..default()
+        };
+        orbcam.apply_input(OrbitInputDelta {
+            yaw: 0.5,
+            surface: Quat::from_rotation_x(0.8),
+            pan: Vec2::new(0.1, 0.2),
+            ..default()
+        });
+
+        assert!(orbcam
+            .up
+            .target
+            .abs_diff_eq(Quat::from_rotation_y(0.5), 1e-6));
+    }
+
+    #[test]
+    fn plane_min_is_height_above_the_plane() {
+        let up = Quat::from_rotation_y(0.4);
+        let low = OrbitSurface::Plane.place(up, 1.0, Vec3::new(30.0, -2.0, 40.0), 3.0);
+        assert!((low.y - 3.0).abs() < 1e-5);
+
+        // Far out a sphere's radial clamp would never lift it.
+        let high = OrbitSurface::Plane.place(up, 1.0, Vec3::new(30.0, 4.0, 40.0), 3.0);
+        assert!((high.y - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ellipsoid_normals_lead_back_to_up() {
+        let up = tilted();
+        let point = EARTHLIKE.place(up, 0.0, Vec3::ZERO, 0.0);
+
+        let normal = EARTHLIKE.normal_at(point, 0.0);
+        assert!(normal.abs_diff_eq(up * Vec3::Y, 1e-5));
+        // Which isn't the direction to the point.
+        assert!(!point.normalize().abs_diff_eq(normal, 1e-2));
+    }
+
+    #[test]
+    fn ellipsoid_altitude_is_measured_along_the_normal() {
+        let up = tilted();
+        let normal = up * Vec3::Y;
+        let radii = Vec3::new(10.0, 7.0, 10.0);
+
+        let position = EARTHLIKE.place(up, 0.5, Vec3::ZERO, 2.0);
+        let below = position - normal * 2.0;
+        assert!(((below / radii).length() - 1.0).abs() < 1e-5);
+
+        // Already high enough, so left where it is.
+        let surface = EARTHLIKE.place(up, 0.0, Vec3::ZERO, 0.0);
+        let high = EARTHLIKE.place(up, 3.0, Vec3::ZERO, 2.0);
+        assert!(high.abs_diff_eq(surface + normal * 3.0, 1e-4));
+    }
+
+    /// Like [`OrbitCam::surface_hit`], with the cursor as a direction in view space.
+    fn cursor_hit(orbcam: &OrbitCam, cursor: Vec3) -> Option<Vec3> {
+        let view = orbcam.target_transform();
+        let ray = Ray3d::new(
+            view.translation - orbcam.focus_position.target,
+            Dir3::new(view.rotation * cursor).unwrap(),
+        );
+        orbcam.surface.hit(ray, orbcam.target_height.target)
+    }
+
+    #[test]
+    fn grabbed_points_stay_under_the_cursor() {
+        let cursor = Vec3::new(0.1, -0.2, -1.0);
+
+        for (surface, height) in [
+            (OrbitSurface::Sphere, 5.0),
+            (OrbitSurface::Plane, 0.0),
+            (EARTHLIKE, 0.5),
+        ] {
+            let mut orbcam = OrbitCam {
+                surface,
+                ..default()
+            };
+            orbcam.up.hard_set(tilted());
+            orbcam.inclination.hard_set(0.6);
+            orbcam.distance.hard_set(12.0);
+            orbcam.target_height.hard_set(height);
+
+            // The point under the cursor, remembered the way grabs are.
+            let under_cursor = |orbcam: &OrbitCam| {
+                let hit = cursor_hit(orbcam, cursor).unwrap();
+                surface.grab_anchor(hit, orbcam.focus_position.target)
+            };
+            let anchor = under_cursor(&orbcam);
+
+            // Drag the surface out from under the cursor.
+            orbcam.apply_input(OrbitInputDelta {
+                surface: Quat::from_rotation_y(0.05) * Quat::from_rotation_x(0.04),
+                shift: Vec3::new(0.6, 0.0, -0.4),
+                ..default()
+            });
+            assert!(!under_cursor(&orbcam).abs_diff_eq(anchor, 1e-2));
+
+            // Held for a few frames, which ellipsoids need to settle.
+            for _ in 0..4 {
+                let hit = cursor_hit(&orbcam, cursor).unwrap();
+                let focus = orbcam.focus_position.target;
+                let (rotation, shift) = surface.grab(anchor, hit, focus, height);
+                orbcam.apply_input(OrbitInputDelta {
+                    surface: rotation,
+                    shift,
+                    ..default()
+                });
+            }
+            assert!(
+                under_cursor(&orbcam).abs_diff_eq(anchor, 1e-3),
+                "{surface:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn tiny_grabs_still_turn() {
+        let from = Vec3::new(0.0, 1.0, 1e-4).normalize();
+        let rotation = rotation_arc(Vec3::Y, from);
+        assert!((rotation * Vec3::Y).abs_diff_eq(from, 1e-7));
+        assert!(!(rotation * Vec3::Y).abs_diff_eq(Vec3::Y, 5e-5));
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+/// Terrain height over an [`OrbitSurface`](crate::OrbitSurface).
+///
+/// Heights are in the same terms as `OrbitCam::min`: distance from the focus
+/// for spheres, and height above the surface for planes and ellipsoids.
+pub trait SurfaceHeight: Send + Sync + 'static {
+    /// The height at `point`, which is a unit direction from the focus on
+    /// spheres and ellipsoids, and a position in the body's frame on planes.
+    fn height_at(&self, point: Vec3) -> f32;
+}
+
+impl<F: Fn(Vec3) -> f32 + Send + Sync + 'static> SurfaceHeight for F {
+    fn height_at(&self, point: Vec3) -> f32 {
+        self(point)
+    }
+}
+
+/// Keeps cameras above terrain, under both the camera and its orbit point.
+///
+/// As a resource every camera avoids the same terrain. Cameras orbiting
+/// other bodies can carry their own as a component, which is used instead.
+#[derive(Resource, Component, Clone)]
+pub struct OrbitCamTerrain {
+    pub height: Arc<dyn SurfaceHeight>,
+    /// How far above the terrain to keep the camera.
+    pub clearance: f32,
+    /// Moves `target_height` to the terrain under the orbit point, so the
+    /// camera orbits the ground rather than a fixed height.
+    pub track_height: bool,
+}
+
+impl OrbitCamTerrain {
+    pub fn new(height: impl SurfaceHeight, clearance: f32) -> Self {
+        OrbitCamTerrain {
+            height: Arc::new(height),
+            clearance,
+            track_height: false,
+        }
+    }
+
+    /// The lowest the camera may go over `point`.
+    pub fn floor(&self, point: Vec3) -> f32 {
+        self.height.height_at(point) + self.clearance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrbitCam, OrbitSurface};
+
+    fn drive(orbcam: &mut OrbitCam, terrain: &OrbitCamTerrain) -> Vec3 {
+        let mut translation = Vec3::ZERO;
+        for _ in 0..30 {
+            translation = orbcam.drive_over(0.1, Some(terrain)).translation;
+        }
+        translation
+    }
+
+    #[test]
+    fn keeps_clear_of_terrain_on_spheres() {
+        // A ridge under the camera, which sits towards +Z of the orbit point.
+        let ridge = OrbitCamTerrain::new(|dir: Vec3| if dir.z > 0.5 { 5.0 } else { 1.0 }, 0.5);
+        let camera = drive(&mut OrbitCam::default(), &ridge);
+        assert!(camera.length() >= 5.5 - 1e-4);
+
+        // A peak under the orbit point, which is straight up from the focus.
+        let mut peak = OrbitCamTerrain::new(|dir: Vec3| if dir.y > 0.9 { 6.0 } else { 1.0 }, 0.5);
+        let camera = drive(&mut OrbitCam::default(), &peak);
+        assert!(camera.length() >= 6.5 - 1e-4);
+
+        peak.track_height = true;
+        let mut orbcam = OrbitCam::default();
+        drive(&mut orbcam, &peak);
+        assert_eq!(orbcam.target_height.target, 6.0);
+    }
+
+    #[test]
+    fn keeps_clear_of_terrain_on_planes() {
+        let plane = || {
+            let mut orbcam = OrbitCam {
+                surface: OrbitSurface::Plane,
+                ..default()
+            };
+            orbcam.focus_position.hard_set(Vec3::X * 10.0);
+            orbcam
+        };
+
+        // A ridge under the camera, which sits towards +Z of the focus.
+        let ridge = OrbitCamTerrain::new(|point: Vec3| if point.z > 2.0 { 5.0 } else { 0.0 }, 0.5);
+        let camera = drive(&mut plane(), &ridge);
+        assert!(camera.y >= 5.5 - 1e-4);
+
+        // A mesa under the focus only.
+        let under_focus = |point: Vec3| (point - Vec3::X * 10.0).length() < 1.0;
+        let mut mesa = OrbitCamTerrain::new(
+            move |point: Vec3| if under_focus(point) { 4.0 } else { 0.0 },
+            0.5,
+        );
+        let camera = drive(&mut plane(), &mesa);
+        assert!(camera.y >= 4.5 - 1e-4);
+
+        mesa.track_height = true;
+        let mut orbcam = plane();
+        drive(&mut orbcam, &mesa);
+        assert_eq!(orbcam.target_height.target, 4.0);
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::{
+    ecs::system::RunSystemOnce,
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    render::camera::{camera_system, ManualTextureViews},
+    window::{
+        PrimaryWindow, WindowCreated, WindowResized, WindowResolution, WindowScaleFactorChanged,
+    },
+};
+
+use crate::{OrbitCamConfig, OrbitCamFocusedViewport, OrbitCamInputRouting, OrbitCamRebind};
+
+/// A world with everything the input systems read, and no input yet.
+pub(crate) fn input_world(config: OrbitCamConfig) -> World {
+    let mut world = World::new();
+    world.init_resource::<Time>();
+    world.init_resource::<Events<MouseWheel>>();
+    world.init_resource::<Events<MouseMotion>>();
+    world.init_resource::<OrbitCamInputRouting>();
+    world.init_resource::<OrbitCamFocusedViewport>();
+    world.init_resource::<OrbitCamRebind>();
+    world.init_resource::<ButtonInput<MouseButton>>();
+    world.init_resource::<ButtonInput<KeyCode>>();
+    world.insert_resource(config);
+    world
+}
+
+/// Spawns a focused 800 by 600 primary window with the cursor at `cursor`.
+pub(crate) fn spawn_window(world: &mut World, cursor: Vec2) -> Entity {
+    let mut window = Window {
+        resolution: WindowResolution::new(800.0, 600.0),
+        ..default()
+    };
+    window.set_cursor_position(Some(cursor));
+    world.spawn((window, PrimaryWindow)).id()
+}
+
+/// Spawns `camera` along with `bundle`, and works out its viewport.
+pub(crate) fn spawn_view(world: &mut World, camera: Camera, bundle: impl Bundle) -> Entity {
+    world.init_resource::<Assets<Image>>();
+    world.init_resource::<ManualTextureViews>();
+    world.init_resource::<Events<WindowCreated>>();
+    world.init_resource::<Events<WindowResized>>();
+    world.init_resource::<Events<WindowScaleFactorChanged>>();
+    world.init_resource::<Events<AssetEvent<Image>>>();
+
+    let entity = world.spawn((camera, Projection::default(), bundle)).id();
+    world.run_system_once(camera_system::<Projection>).unwrap();
+    entity
+}